@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// A single cached response body plus when it was fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    body: String,
+    fetched_at: u64,
+}
+
+/// Outcome of a [`Cache::fetch`] call, distinguishing a cache hit from a
+/// freshly fetched value so callers can log/instrument either path.
+#[derive(Debug, Clone)]
+pub enum CacheResult {
+    Hit(String),
+    Miss(String),
+}
+
+impl CacheResult {
+    /// The body, regardless of whether it came from the cache or the network.
+    pub fn into_inner(self) -> String {
+        match self {
+            CacheResult::Hit(body) | CacheResult::Miss(body) => body,
+        }
+    }
+
+    pub fn was_hit(&self) -> bool {
+        matches!(self, CacheResult::Hit(_))
+    }
+}
+
+/// On-disk response cache keyed by request URL (including query string).
+///
+/// Entries are stored as a single JSON file under the OS cache directory
+/// (e.g. `~/.cache/macro-factor-rs/http-cache.json` on Linux), so repeated
+/// tooling runs within the same TTL window can skip the network entirely.
+#[derive(Clone)]
+pub struct Cache {
+    path: PathBuf,
+}
+
+impl Cache {
+    /// Open (creating if necessary) the default cache file under the OS
+    /// cache directory.
+    pub fn open_default() -> Result<Self> {
+        let dir = dirs::cache_dir()
+            .ok_or_else(|| anyhow!("could not determine OS cache directory"))?
+            .join("macro-factor-rs");
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join("http-cache.json"),
+        })
+    }
+
+    /// Open a cache backed by an explicit file path.
+    pub fn open(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> HashMap<String, CacheEntry> {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, entries: &HashMap<String, CacheEntry>) -> Result<()> {
+        let json = serde_json::to_string(entries)?;
+        std::fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    /// Return the cached body for `key` if it exists and is younger than `ttl`.
+    ///
+    /// A `ttl` of zero always misses, which is the supported way to bypass
+    /// the cache for a single call.
+    pub fn get(&self, key: &str, ttl: Duration) -> Option<String> {
+        if ttl.is_zero() {
+            return None;
+        }
+        let entries = self.load();
+        let entry = entries.get(key)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        if now.saturating_sub(entry.fetched_at) < ttl.as_secs() {
+            Some(entry.body.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Store `body` for `key`, overwriting any existing entry.
+    pub fn set(&self, key: &str, body: &str) -> Result<()> {
+        let mut entries = self.load();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        entries.insert(
+            key.to_string(),
+            CacheEntry {
+                body: body.to_string(),
+                fetched_at: now,
+            },
+        );
+        self.save(&entries)
+    }
+
+    /// Fetch `key` from the cache, falling back to `fetch` on a miss and
+    /// persisting the result.
+    pub async fn fetch<F, Fut>(&self, key: &str, ttl: Duration, fetch: F) -> Result<CacheResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<String>>,
+    {
+        if let Some(body) = self.get(key, ttl) {
+            return Ok(CacheResult::Hit(body));
+        }
+        let body = fetch().await?;
+        self.set(key, &body)?;
+        Ok(CacheResult::Miss(body))
+    }
+
+    /// Remove every cached entry.
+    pub fn clear(&self) -> Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Build a stable cache key from a URL and its query parameters.
+///
+/// Parameters are sorted so that e.g. `pageToken` being appended in a
+/// different order doesn't produce a distinct cache entry.
+pub fn cache_key(url: &str, params: &[(&str, &str)]) -> String {
+    let mut sorted: Vec<&(&str, &str)> = params.iter().collect();
+    sorted.sort_by_key(|(k, v)| (*k, *v));
+    let mut key = url.to_string();
+    for (k, v) in sorted {
+        key.push('?');
+        key.push_str(k);
+        key.push('=');
+        key.push_str(v);
+    }
+    key
+}
@@ -1,48 +1,229 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Local, NaiveDate, Timelike};
+use chrono::{DateTime, Local, NaiveDate, Timelike, Utc};
 use reqwest::Client;
 use serde_json::{json, Value};
 
-use crate::auth::FirebaseAuth;
+use crate::auth::{FirebaseAuth, FirebaseAuthConfig};
+use crate::cache::{cache_key, Cache};
 use crate::firestore::{
-    parse_document, parse_firestore_fields, to_firestore_fields, FirestoreClient,
+    parse_document, parse_firestore_fields, to_firestore_fields, to_firestore_value, Document,
+    FirestoreClient,
 };
+use crate::local_search::LocalFoodIndex;
 use crate::models::*;
+use crate::store::{RecordKind, Store};
 
 const TYPESENSE_HOST: &str = "https://oewdzs50x93n2c4mp.a1.typesense.net";
 const TYPESENSE_API_KEY: &str = "4tKoPwBN6YaPXZDeQ7AyDfZbrjPbGMmG";
 
+/// Default TTL for the in-memory yearly-document cache (see
+/// [`MacroFactorClient::with_cache_ttl`]).
+const DEFAULT_YEAR_DOC_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// A live change to a day's food log, as surfaced by
+/// [`MacroFactorClient::watch_food_log`].
+#[derive(Debug, Clone)]
+pub enum FoodLogEvent {
+    Added(FoodEntry),
+    Updated(FoodEntry),
+    /// The removed entry's id.
+    Removed(String),
+}
+
 #[derive(Clone)]
 pub struct MacroFactorClient {
     pub auth: FirebaseAuth,
     pub firestore: FirestoreClient,
     user_id: Option<String>,
+    cache: Option<Cache>,
+    /// In-memory cache of yearly `scale`/`nutrition`/`steps` documents, keyed
+    /// by document path, so e.g. a dashboard re-querying overlapping ranges
+    /// every minute doesn't refetch the same document every time.
+    year_doc_cache: HashMap<String, (Document, Instant)>,
+    year_doc_cache_ttl: Duration,
+    /// Durable offline mirror of weight/nutrition/steps/food data (see
+    /// [`with_default_store`](Self::with_default_store)). Distinct from
+    /// `cache`/`year_doc_cache`, which only hold recently-seen responses:
+    /// the store is meant to survive indefinitely and to be read without
+    /// network access.
+    store: Option<Store>,
+    /// Offline fallback used by [`search`](Self::search) when the Typesense
+    /// request itself can't be made (see [`with_local_index`](Self::with_local_index)).
+    local_index: Option<LocalFoodIndex>,
 }
 
 impl MacroFactorClient {
     pub fn new(refresh_token: String) -> Self {
-        let auth = FirebaseAuth::new(refresh_token);
+        let auth = FirebaseAuth::new(refresh_token, FirebaseAuthConfig::default());
         let firestore = FirestoreClient::new(auth.clone());
         Self {
             auth,
             firestore,
             user_id: None,
+            cache: None,
+            year_doc_cache: HashMap::new(),
+            year_doc_cache_ttl: DEFAULT_YEAR_DOC_CACHE_TTL,
+            store: None,
+            local_index: None,
         }
     }
 
     /// Sign in with email and password.
     pub async fn login(email: &str, password: &str) -> Result<Self> {
-        let auth = FirebaseAuth::sign_in_with_email(email, password).await?;
+        let auth = FirebaseAuth::sign_in_with_email(email, password, FirebaseAuthConfig::default()).await?;
+        let firestore = FirestoreClient::new(auth.clone());
+        Ok(Self {
+            auth,
+            firestore,
+            user_id: None,
+            cache: None,
+            year_doc_cache: HashMap::new(),
+            year_doc_cache_ttl: DEFAULT_YEAR_DOC_CACHE_TTL,
+            store: None,
+            local_index: None,
+        })
+    }
+
+    /// Enable on-disk response caching (Firestore reads and food search)
+    /// using the default OS cache directory.
+    pub fn with_default_cache(mut self) -> Result<Self> {
+        let cache = Cache::open_default()?;
+        self.firestore = self.firestore.clone().with_cache(cache.clone());
+        self.cache = Some(cache);
+        Ok(self)
+    }
+
+    /// Enable the durable offline store (see [`sync_range`](Self::sync_range)
+    /// and the `local_*` getters) using the default OS data directory.
+    pub fn with_default_store(mut self) -> Result<Self> {
+        self.store = Some(Store::open_default()?);
+        Ok(self)
+    }
+
+    /// Enable an offline fallback for [`search`](Self::search) (and so
+    /// [`search_foods`](Self::search_foods)/[`search_foods_ttl`](Self::search_foods_ttl)):
+    /// when the Typesense request can't be made at all (no connectivity, a
+    /// timed-out connection), queries are served from a fuzzy, in-memory
+    /// index built from `foods` instead of failing outright.
+    pub fn with_local_index(mut self, foods: Vec<SearchFoodResult>) -> Self {
+        self.local_index = Some(LocalFoodIndex::build(foods));
+        self
+    }
+
+    fn store(&self) -> Result<&Store> {
+        self.store
+            .as_ref()
+            .ok_or_else(|| anyhow!("no local store configured — call with_default_store() first"))
+    }
+
+    /// Stamp a raw food entry object with a fresh `(counter, client_id)`
+    /// logical version (the `"vc"`/`"vi"` fields), for
+    /// [`sync_incremental`](Self::sync_incremental)'s last-writer-wins merge.
+    /// A no-op when no local store is configured, since the version counter
+    /// is persisted there.
+    fn stamp_version(&self, obj: &mut serde_json::Map<String, Value>) -> Result<()> {
+        let Some(store) = self.store.as_ref() else {
+            return Ok(());
+        };
+        obj.insert("vc".to_string(), json!(store.next_counter()?));
+        obj.insert("vi".to_string(), json!(store.client_id()?));
+        Ok(())
+    }
+
+    /// Set the TTL for the in-memory yearly-document cache used by
+    /// [`get_weight_entries`](Self::get_weight_entries),
+    /// [`get_nutrition`](Self::get_nutrition), and [`get_steps`](Self::get_steps).
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.year_doc_cache_ttl = ttl;
+        self
+    }
+
+    /// Drop all entries from the in-memory yearly-document cache.
+    pub fn clear_cache(&mut self) {
+        self.year_doc_cache.clear();
+    }
+
+    /// Fetch a `scale`/`nutrition`/`steps` yearly document, reusing a cached
+    /// copy younger than the configured TTL. Writes to these documents must
+    /// evict the corresponding entry so stale reads can't resurrect deleted
+    /// data — see the `remove`/overwrite calls in `log_weight` etc.
+    async fn get_document_cached(&mut self, path: &str) -> Result<Document> {
+        if let Some((doc, fetched_at)) = self.year_doc_cache.get(path) {
+            if fetched_at.elapsed() < self.year_doc_cache_ttl {
+                return Ok(doc.clone());
+            }
+        }
+        let doc = self.firestore.get_document(path).await?;
+        self.year_doc_cache
+            .insert(path.to_string(), (doc.clone(), Instant::now()));
+        Ok(doc)
+    }
+
+    /// Fetch many documents — e.g. a multi-year span of `scale`/`nutrition`/
+    /// `steps` documents, or several `food/{date}` documents — in a single
+    /// `batchGet` round trip instead of one `get_document` per path.
+    ///
+    /// Reuses any copy still within the configured TTL and only batch-fetches
+    /// the rest. Missing documents are simply absent from the returned map.
+    async fn get_documents_cached(&mut self, paths: &[String]) -> Result<HashMap<String, Document>> {
+        let mut found = HashMap::new();
+        let mut to_fetch = Vec::new();
+
+        for path in paths {
+            if let Some((doc, fetched_at)) = self.year_doc_cache.get(path) {
+                if fetched_at.elapsed() < self.year_doc_cache_ttl {
+                    found.insert(path.clone(), doc.clone());
+                    continue;
+                }
+            }
+            to_fetch.push(path.clone());
+        }
+
+        if !to_fetch.is_empty() {
+            let docs = self.firestore.batch_get_documents(&to_fetch).await?;
+            for (path, doc) in to_fetch.into_iter().zip(docs) {
+                if let Some(doc) = doc {
+                    self.year_doc_cache
+                        .insert(path.clone(), (doc.clone(), Instant::now()));
+                    found.insert(path, doc);
+                }
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Load a client from a session previously saved with
+    /// [`save_session`](Self::save_session), refreshing the ID token if it
+    /// has expired.
+    ///
+    /// Only errors if the stored refresh token itself is rejected — in that
+    /// case callers should fall back to [`login`](Self::login).
+    pub async fn from_session(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let auth = FirebaseAuth::load(path)?;
+        // Eagerly validate/refresh so a dead refresh token surfaces now.
+        auth.get_id_token().await?;
         let firestore = FirestoreClient::new(auth.clone());
         Ok(Self {
             auth,
             firestore,
             user_id: None,
+            cache: None,
+            year_doc_cache: HashMap::new(),
+            year_doc_cache_ttl: DEFAULT_YEAR_DOC_CACHE_TTL,
+            store: None,
+            local_index: None,
         })
     }
 
+    /// Persist the current session to `path` so a future run can skip login.
+    pub async fn save_session(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.auth.save(path).await
+    }
+
     pub async fn get_user_id(&mut self) -> Result<String> {
         if let Some(ref uid) = self.user_id {
             return Ok(uid.clone());
@@ -94,15 +275,18 @@ impl MacroFactorClient {
         let uid = self.get_user_id().await?;
         let mut entries = Vec::new();
 
-        // Collect all years in the range
+        // Collect all years in the range and fetch them in one batchGet.
         let start_year = start.format("%Y").to_string().parse::<i32>()?;
         let end_year = end.format("%Y").to_string().parse::<i32>()?;
+        let paths: Vec<String> = (start_year..=end_year)
+            .map(|year| format!("users/{}/scale/{}", uid, year))
+            .collect();
+        let docs = self.get_documents_cached(&paths).await?;
 
         for year in start_year..=end_year {
             let path = format!("users/{}/scale/{}", uid, year);
-            let doc = match self.firestore.get_document(&path).await {
-                Ok(d) => d,
-                Err(_) => continue,
+            let Some(doc) = docs.get(&path) else {
+                continue;
             };
 
             if let Some(ref fields) = doc.fields {
@@ -154,12 +338,15 @@ impl MacroFactorClient {
 
         let start_year = start.format("%Y").to_string().parse::<i32>()?;
         let end_year = end.format("%Y").to_string().parse::<i32>()?;
+        let paths: Vec<String> = (start_year..=end_year)
+            .map(|year| format!("users/{}/nutrition/{}", uid, year))
+            .collect();
+        let docs = self.get_documents_cached(&paths).await?;
 
         for year in start_year..=end_year {
             let path = format!("users/{}/nutrition/{}", uid, year);
-            let doc = match self.firestore.get_document(&path).await {
-                Ok(d) => d,
-                Err(_) => continue,
+            let Some(doc) = docs.get(&path) else {
+                continue;
             };
 
             if let Some(ref fields) = doc.fields {
@@ -213,83 +400,112 @@ impl MacroFactorClient {
         let date_str = date.format("%Y-%m-%d").to_string();
         let path = format!("users/{}/food/{}", uid, date_str);
 
-        let doc = match self.firestore.get_document(&path).await {
+        let doc = match self.get_document_cached(&path).await {
             Ok(d) => d,
             Err(e) if e.to_string().contains("404") => return Ok(Vec::new()),
             Err(e) => return Err(e),
         };
+
+        Ok(parse_food_entries(date, &doc))
+    }
+
+    /// Get food log entries for every date in `start..=end` in a single
+    /// batch Firestore request, instead of one `get_document` per day.
+    pub async fn get_food_log_range(
+        &mut self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<FoodEntry>> {
+        let uid = self.get_user_id().await?;
+
+        let mut dates = Vec::new();
+        let mut date = start;
+        while date <= end {
+            dates.push(date);
+            date = date.succ_opt().ok_or_else(|| anyhow!("date out of range"))?;
+        }
+
+        let paths: Vec<String> = dates
+            .iter()
+            .map(|d| format!("users/{}/food/{}", uid, d.format("%Y-%m-%d")))
+            .collect();
+        let docs = self.get_documents_cached(&paths).await?;
+
         let mut entries = Vec::new();
+        for (date, path) in dates.iter().zip(paths.iter()) {
+            if let Some(doc) = docs.get(path) {
+                entries.extend(parse_food_entries(*date, doc));
+            }
+        }
+
+        entries.sort_by(|a, b| (a.date, &a.hour, &a.minute).cmp(&(b.date, &b.hour, &b.minute)));
+        Ok(entries)
+    }
 
-        if let Some(ref fields) = doc.fields {
-            let parsed = parse_firestore_fields(&Value::Object(fields.clone()));
-            if let Some(map) = parsed.as_object() {
-                for (key, val) in map {
-                    if key.starts_with('_') {
-                        continue;
+    /// Subscribe to live changes to a day's food log, instead of polling
+    /// [`get_food_log`](Self::get_food_log).
+    ///
+    /// The underlying `Listen` stream reports whole-document snapshots, so
+    /// this diffs each snapshot against the last-seen entries to emit
+    /// per-entry [`FoodLogEvent`]s.
+    pub async fn watch_food_log(
+        &mut self,
+        date: NaiveDate,
+    ) -> Result<impl futures_core::Stream<Item = Result<FoodLogEvent>> + '_> {
+        let uid = self.get_user_id().await?;
+        let date_str = date.format("%Y-%m-%d").to_string();
+        let path = format!("users/{}/food/{}", uid, date_str);
+        let target = self.firestore.document_full_name(&path);
+
+        Ok(async_stream::try_stream! {
+            use futures_util::StreamExt;
+
+            let mut known: HashMap<String, FoodEntry> = HashMap::new();
+            let raw = self.firestore.listen(vec![target]);
+            futures_util::pin_mut!(raw);
+
+            while let Some(change) = raw.next().await {
+                match change? {
+                    crate::firestore::DocumentChange::Added(doc)
+                    | crate::firestore::DocumentChange::Modified(doc) => {
+                        let mut seen = std::collections::HashSet::new();
+                        for entry in parse_food_entries(date, &doc) {
+                            seen.insert(entry.entry_id.clone());
+                            match known.get(&entry.entry_id) {
+                                Some(prev) if *prev == entry => {}
+                                Some(_) => {
+                                    known.insert(entry.entry_id.clone(), entry.clone());
+                                    yield FoodLogEvent::Updated(entry);
+                                }
+                                None => {
+                                    known.insert(entry.entry_id.clone(), entry.clone());
+                                    yield FoodLogEvent::Added(entry);
+                                }
+                            }
+                        }
+                        let removed: Vec<String> = known
+                            .keys()
+                            .filter(|id| !seen.contains(*id))
+                            .cloned()
+                            .collect();
+                        for id in removed {
+                            known.remove(&id);
+                            yield FoodLogEvent::Removed(id);
+                        }
                     }
-                    if let Some(obj) = val.as_object() {
-                        let parse_num = |k: &str| -> Option<f64> {
-                            obj.get(k).and_then(|v| {
-                                v.as_f64()
-                                    .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
-                            })
-                        };
-                        let parse_str =
-                            |k: &str| obj.get(k).and_then(|v| v.as_str()).map(String::from);
-
-                        let serving_grams = parse_num("g");
-                        let user_qty = parse_num("y");
-                        let unit_weight = parse_num("w");
-
-                        let deleted = obj.get("d").and_then(|v| v.as_bool());
-
-                        entries.push(FoodEntry {
-                            date,
-                            entry_id: key.clone(),
-                            name: parse_str("t"),
-                            brand: parse_str("b"),
-                            calories_raw: parse_num("c"),
-                            protein_raw: parse_num("p"),
-                            carbs_raw: parse_num("e"),
-                            fat_raw: parse_num("f"),
-                            serving_grams,
-                            user_qty,
-                            unit_weight,
-                            quantity: parse_num("q"),
-                            serving_unit: parse_str("s"),
-                            hour: parse_str("h"),
-                            minute: parse_str("mi"),
-                            source_type: parse_str("k"),
-                            food_id: parse_str("id"),
-                            deleted,
-                        });
+                    crate::firestore::DocumentChange::Removed(_) => {
+                        for id in known.keys().cloned().collect::<Vec<_>>() {
+                            known.remove(&id);
+                            yield FoodLogEvent::Removed(id);
+                        }
                     }
+                    // No per-entry signal to emit — just target bookkeeping
+                    // (initial snapshot complete, or a server-requested
+                    // resync).
+                    crate::firestore::DocumentChange::TargetChange(_) => {}
                 }
             }
-        }
-
-        // Sort by hour:minute
-        entries.sort_by(|a, b| {
-            let time_a = (
-                a.hour.as_deref().unwrap_or("0").parse::<u32>().unwrap_or(0),
-                a.minute
-                    .as_deref()
-                    .unwrap_or("0")
-                    .parse::<u32>()
-                    .unwrap_or(0),
-            );
-            let time_b = (
-                b.hour.as_deref().unwrap_or("0").parse::<u32>().unwrap_or(0),
-                b.minute
-                    .as_deref()
-                    .unwrap_or("0")
-                    .parse::<u32>()
-                    .unwrap_or(0),
-            );
-            time_a.cmp(&time_b)
-        });
-
-        Ok(entries)
+        })
     }
 
     /// Get step counts for a date range.
@@ -300,12 +516,15 @@ impl MacroFactorClient {
 
         let start_year = start.format("%Y").to_string().parse::<i32>()?;
         let end_year = end.format("%Y").to_string().parse::<i32>()?;
+        let paths: Vec<String> = (start_year..=end_year)
+            .map(|year| format!("users/{}/steps/{}", uid, year))
+            .collect();
+        let docs = self.get_documents_cached(&paths).await?;
 
         for year in start_year..=end_year {
             let path = format!("users/{}/steps/{}", uid, year);
-            let doc = match self.firestore.get_document(&path).await {
-                Ok(d) => d,
-                Err(_) => continue,
+            let Some(doc) = docs.get(&path) else {
+                continue;
             };
 
             if let Some(ref fields) = doc.fields {
@@ -347,6 +566,120 @@ impl MacroFactorClient {
         Ok(entries)
     }
 
+    /// Pull weight/nutrition/steps/food data for a date range from Firestore
+    /// and mirror it into the local offline store (requires
+    /// [`with_default_store`](Self::with_default_store)).
+    ///
+    /// After this completes, `local_weight`/`local_nutrition`/`local_steps`/
+    /// `local_food_log` can serve that range without network access.
+    pub async fn sync_range(&mut self, start: NaiveDate, end: NaiveDate) -> Result<()> {
+        self.store()?;
+
+        let weight = self.get_weight_entries(start, end).await?;
+        for entry in &weight {
+            self.store()?.upsert(
+                &weight_record_id(entry.date),
+                RecordKind::Weight,
+                Utc::now(),
+                serde_json::to_value(entry)?,
+            )?;
+        }
+
+        let nutrition = self.get_nutrition(start, end).await?;
+        for entry in &nutrition {
+            self.store()?.upsert(
+                &nutrition_record_id(entry.date),
+                RecordKind::Nutrition,
+                Utc::now(),
+                serde_json::to_value(entry)?,
+            )?;
+        }
+
+        let steps = self.get_steps(start, end).await?;
+        for entry in &steps {
+            self.store()?.upsert(
+                &steps_record_id(entry.date),
+                RecordKind::Steps,
+                Utc::now(),
+                serde_json::to_value(entry)?,
+            )?;
+        }
+
+        let mut date = start;
+        while date <= end {
+            let food = self.get_food_log(date).await?;
+            for entry in &food {
+                self.store()?.upsert(
+                    &food_record_id(date, &entry.entry_id),
+                    RecordKind::Food,
+                    Utc::now(),
+                    serde_json::to_value(entry)?,
+                )?;
+            }
+            date = date
+                .succ_opt()
+                .ok_or_else(|| anyhow!("date out of range"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Weight entries previously pulled into the local store by
+    /// [`sync_range`](Self::sync_range), for a date range.
+    pub fn local_weight(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<ScaleEntry>> {
+        let mut entries: Vec<ScaleEntry> = self
+            .store()?
+            .records(RecordKind::Weight)?
+            .into_iter()
+            .filter_map(|r| r.payload.and_then(|p| serde_json::from_value(p).ok()))
+            .filter(|e: &ScaleEntry| e.date >= start && e.date <= end)
+            .collect();
+        entries.sort_by_key(|e| e.date);
+        Ok(entries)
+    }
+
+    /// Nutrition summaries previously pulled into the local store by
+    /// [`sync_range`](Self::sync_range), for a date range.
+    pub fn local_nutrition(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<NutritionSummary>> {
+        let mut entries: Vec<NutritionSummary> = self
+            .store()?
+            .records(RecordKind::Nutrition)?
+            .into_iter()
+            .filter_map(|r| r.payload.and_then(|p| serde_json::from_value(p).ok()))
+            .filter(|e: &NutritionSummary| e.date >= start && e.date <= end)
+            .collect();
+        entries.sort_by_key(|e| e.date);
+        Ok(entries)
+    }
+
+    /// Step counts previously pulled into the local store by
+    /// [`sync_range`](Self::sync_range), for a date range.
+    pub fn local_steps(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<StepEntry>> {
+        let mut entries: Vec<StepEntry> = self
+            .store()?
+            .records(RecordKind::Steps)?
+            .into_iter()
+            .filter_map(|r| r.payload.and_then(|p| serde_json::from_value(p).ok()))
+            .filter(|e: &StepEntry| e.date >= start && e.date <= end)
+            .collect();
+        entries.sort_by_key(|e| e.date);
+        Ok(entries)
+    }
+
+    /// Food log entries previously pulled into the local store by
+    /// [`sync_range`](Self::sync_range), for a single date.
+    pub fn local_food_log(&self, date: NaiveDate) -> Result<Vec<FoodEntry>> {
+        let mut entries: Vec<FoodEntry> = self
+            .store()?
+            .records(RecordKind::Food)?
+            .into_iter()
+            .filter_map(|r| r.payload.and_then(|p| serde_json::from_value(p).ok()))
+            .filter(|e: &FoodEntry| e.date == date)
+            .collect();
+        entries.sort_by(|a, b| (&a.hour, &a.minute).cmp(&(&b.hour, &b.minute)));
+        Ok(entries)
+    }
+
     /// Get the current macro/calorie goals from the user's planner.
     pub async fn get_goals(&mut self) -> Result<Goals> {
         let profile = self.get_profile().await?;
@@ -390,22 +723,65 @@ impl MacroFactorClient {
         })
     }
 
+    /// Get a unified view of a single day: the food log, weight, steps, and
+    /// active goals, with macro totals already rolled up — a single call
+    /// for a day screen instead of four.
+    pub async fn get_day(&mut self, date: NaiveDate) -> Result<DaySummary> {
+        let food = self.get_food_log(date).await?;
+        let weight = self.get_weight_entries(date, date).await?.into_iter().next();
+        let steps = self.get_steps(date, date).await?.into_iter().next();
+        // Not every user has a planner set up yet — treat that as "no goals"
+        // rather than failing the whole day view.
+        let goals = self.get_goals().await.ok();
+
+        let (calories_consumed, protein_consumed, carbs_consumed, fat_consumed) =
+            aggregate_macros(&food);
+
+        Ok(DaySummary {
+            date,
+            food,
+            weight,
+            steps,
+            goals,
+            calories_consumed,
+            protein_consumed,
+            carbs_consumed,
+            fat_consumed,
+        })
+    }
+
     /// Write a food entry to Firestore.
     ///
     /// This is the shared implementation used by `log_food` and `log_searched_food`.
-    async fn write_food_entry(&mut self, logged_at: DateTime<Local>, entry: Value) -> Result<()> {
+    async fn write_food_entry(&mut self, logged_at: DateTime<Local>, mut entry: Value) -> Result<()> {
         let uid = self.get_user_id().await?;
-        let date_str = logged_at.format("%Y-%m-%d").to_string();
+        let date = logged_at.date_naive();
+        let date_str = date.format("%Y-%m-%d").to_string();
         let path = format!("users/{}/food/{}", uid, date_str);
 
         let ts = logged_at.timestamp_millis();
         let entry_id = format!("{}", ts * 1000);
 
-        let fields = to_firestore_fields(&json!({ &entry_id: entry }));
+        if let Some(obj) = entry.as_object_mut() {
+            self.stamp_version(obj)?;
+        }
+
+        let fields = to_firestore_fields(&json!({ &entry_id: entry.clone() }));
         let field_mask = format!("`{}`", entry_id);
         self.firestore
             .patch_document(&path, fields, &[&field_mask])
             .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let (Some(store), Some(obj)) = (self.store.as_ref(), entry.as_object()) {
+            let parsed = parse_food_entry(date, entry_id.clone(), obj);
+            store.upsert(
+                &food_record_id(date, &entry_id),
+                RecordKind::Food,
+                Utc::now(),
+                serde_json::to_value(&parsed)?,
+            )?;
+        }
 
         Ok(())
     }
@@ -487,6 +863,21 @@ impl MacroFactorClient {
         self.firestore
             .patch_document(&path, fields, &[&field_mask])
             .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let Some(store) = &self.store {
+            store.upsert(
+                &weight_record_id(date),
+                RecordKind::Weight,
+                Utc::now(),
+                serde_json::to_value(&ScaleEntry {
+                    date,
+                    weight: weight_kg,
+                    body_fat,
+                    source: Some("m".to_string()),
+                })?,
+            )?;
+        }
 
         Ok(())
     }
@@ -505,6 +896,11 @@ impl MacroFactorClient {
         self.firestore
             .patch_document(&path, fields, &[&field_mask])
             .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let Some(store) = &self.store {
+            store.tombstone(&weight_record_id(date), RecordKind::Weight, Utc::now())?;
+        }
 
         Ok(())
     }
@@ -543,67 +939,107 @@ impl MacroFactorClient {
         self.firestore
             .patch_document(&path, fields, &[&field_mask])
             .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let Some(store) = &self.store {
+            store.upsert(
+                &nutrition_record_id(date),
+                RecordKind::Nutrition,
+                Utc::now(),
+                serde_json::to_value(&NutritionSummary {
+                    date,
+                    calories: Some(calories),
+                    protein,
+                    carbs,
+                    fat,
+                    sugar: None,
+                    fiber: None,
+                    source: Some("m".to_string()),
+                })?,
+            )?;
+        }
 
         Ok(())
     }
 
     /// Search the food database using Typesense.
     ///
-    /// Searches both `common_foods` and `branded_foods` collections.
-    /// No authentication required — uses the Typesense API key directly.
+    /// A thin wrapper over [`search`](Self::search) using
+    /// [`FoodSearchQuery`]'s defaults (both collections, 10 per page, no
+    /// filter). No authentication required — uses the Typesense API key directly.
     pub async fn search_foods(&self, query: &str) -> Result<Vec<SearchFoodResult>> {
-        let client = Client::new();
-        let url = format!("{}/multi_search", TYPESENSE_HOST);
+        Ok(self.search(FoodSearchQuery::new(query)).await?.results)
+    }
 
-        let body = json!({
-            "searches": [
-                {
-                    "collection": "common_foods",
-                    "q": query,
-                    "query_by": "foodDesc",
-                    "per_page": 10
-                },
-                {
-                    "collection": "branded_foods",
-                    "q": query,
-                    "query_by": "foodDesc,brandName",
-                    "per_page": 10
+    /// Search the food database using Typesense, per the collections,
+    /// pagination, filter, and sort order set on `query`.
+    ///
+    /// No authentication required — uses the Typesense API key directly. If
+    /// the request can't be made at all (no connectivity, a timed-out
+    /// connection) and [`with_local_index`](Self::with_local_index) was
+    /// configured, falls back to the offline index instead of failing —
+    /// `total_common`/`total_branded` are `None` for a fallback result, since
+    /// the local index doesn't track collection totals.
+    pub async fn search(&self, query: FoodSearchQuery) -> Result<FoodSearchPage> {
+        match fetch_typesense_search(&query).await {
+            Ok((text, branded_flags)) => {
+                let data: Value = serde_json::from_str(&text)?;
+                Ok(parse_search_page(&data, &branded_flags))
+            }
+            Err(e) => {
+                if let Some(index) = self.local_index.as_ref().filter(|_| is_connection_error(&e)) {
+                    return Ok(FoodSearchPage {
+                        results: index.search(&query.query, query.per_page as usize),
+                        total_common: None,
+                        total_branded: None,
+                    });
                 }
-            ]
-        });
-
-        let resp = client
-            .post(&url)
-            .header("x-typesense-api-key", TYPESENSE_API_KEY)
-            .json(&body)
-            .send()
-            .await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("Typesense search failed: {} - {}", status, text));
+                Err(e)
+            }
         }
+    }
 
-        let data: Value = resp.json().await?;
-        let mut results = Vec::new();
-
-        if let Some(searches) = data.get("results").and_then(|v| v.as_array()) {
-            for (idx, search) in searches.iter().enumerate() {
-                let branded = idx == 1;
-                if let Some(hits) = search.get("hits").and_then(|v| v.as_array()) {
-                    for hit in hits {
-                        if let Some(doc) = hit.get("document") {
-                            if let Some(result) = parse_typesense_hit(doc, branded) {
-                                results.push(result);
-                            }
-                        }
-                    }
+    /// Like [`search_foods`](Self::search_foods), but serves a cached copy
+    /// keyed on the search term when one exists and is younger than
+    /// `local_ttl` (requires [`with_default_cache`](Self::with_default_cache);
+    /// `local_ttl` of zero always hits the network).
+    pub async fn search_foods_ttl(
+        &self,
+        query: &str,
+        local_ttl: Duration,
+    ) -> Result<Vec<SearchFoodResult>> {
+        let search_query = FoodSearchQuery::new(query);
+        let url = format!("{}/multi_search", TYPESENSE_HOST);
+        let key = cache_key(&url, &[("q", query)]);
+        let branded_flags = collection_branded_flags(search_query.collections);
+
+        if !local_ttl.is_zero() {
+            if let Some(cache) = &self.cache {
+                if let Some(cached) = cache.get(&key, local_ttl) {
+                    let data: Value = serde_json::from_str(&cached)?;
+                    return Ok(parse_search_page(&data, &branded_flags).results);
                 }
             }
         }
 
-        Ok(results)
+        let (text, branded_flags) = fetch_typesense_search(&search_query).await?;
+        if let Some(cache) = &self.cache {
+            cache.set(&key, &text)?;
+        }
+        let data: Value = serde_json::from_str(&text)?;
+        Ok(parse_search_page(&data, &branded_flags).results)
+    }
+
+    /// Import a food from an arbitrary recipe/product web page by reading
+    /// its embedded schema.org JSON-LD nutrition information.
+    ///
+    /// This gives a way to log foods that aren't in the Typesense index —
+    /// the result plugs straight into [`log_searched_food`](Self::log_searched_food)
+    /// like any other search hit.
+    pub async fn import_food_from_url(&self, url: &str) -> Result<SearchFoodResult> {
+        let client = Client::new();
+        let html = client.get(url).send().await?.error_for_status()?.text().await?;
+        crate::recipe::parse_recipe_html(&html, url)
     }
 
     /// Log a food entry from a search result.
@@ -625,67 +1061,72 @@ impl MacroFactorClient {
         let hour = logged_at.hour().to_string();
         let minute = logged_at.minute().to_string();
 
-        // Serving gram weight (this becomes the "g" field — the base for macro values)
-        let serving_grams = serving.gram_weight;
-        // Scale factor from per-100g to per-serving
-        let scale = serving_grams / 100.0;
+        let entry = build_searched_food_entry(food, serving, quantity, &entry_id, &ua_id, &hour, &minute);
 
-        // Grams per one display unit
-        let unit_weight = serving.gram_weight / serving.amount;
-        // Total display units
-        let total_units = quantity * serving.amount;
+        self.write_food_entry(logged_at, entry).await
+    }
 
-        let measurements: Vec<Value> = food
-            .servings
-            .iter()
-            .map(|s| {
-                json!({
-                    "m": s.description,
-                    "q": format!("{:.1}", s.amount),
-                    "w": format!("{}", s.gram_weight)
-                })
-            })
-            .collect();
+    /// Log several search results as one meal in a single atomic write.
+    ///
+    /// Unlike calling [`log_searched_food`](Self::log_searched_food) once per
+    /// item — which issues one PATCH each — this batches all entries into a
+    /// single Firestore `:commit`, so the meal is logged all-or-nothing.
+    pub async fn log_meal(
+        &mut self,
+        logged_at: DateTime<Local>,
+        items: &[(SearchFoodResult, FoodServing, f64)],
+    ) -> Result<()> {
+        let uid = self.get_user_id().await?;
+        let date = logged_at.date_naive();
+        let date_str = date.format("%Y-%m-%d").to_string();
+        let path = format!("users/{}/food/{}", uid, date_str);
 
-        let mut entry = json!({
-            "t": food.name,
-            "b": food.brand.as_deref().unwrap_or(""),
-            "c": format!("{}", food.calories_per_100g * scale),
-            "p": format!("{}", food.protein_per_100g * scale),
-            "e": format!("{}", food.carbs_per_100g * scale),
-            "f": format!("{}", food.fat_per_100g * scale),
-            "g": format!("{}", serving_grams),
-            "w": format!("{}", unit_weight),
-            "y": format!("{}", total_units),
-            "q": format!("{}", serving.amount),
-            "s": serving.description,
-            "u": serving.description,
-            "h": hour,
-            "mi": minute,
-            "k": "t",
-            "id": food.food_id,
-            "ca": &entry_id,
-            "ua": &ua_id,
-            "ef": false,
-            "d": false,
-            "o": false,
-            "fav": false,
-            "x": food.image_id.as_deref().unwrap_or("13"),
-            "m": measurements
-        });
+        let base_ts = logged_at.timestamp_millis();
+        let hour = logged_at.hour().to_string();
+        let minute = logged_at.minute().to_string();
 
-        // Copy all micronutrient values, scaled to serving size
-        if let Some(obj) = entry.as_object_mut() {
-            for (code, val_per_100g) in &food.nutrients_per_100g {
-                // Skip the main macro codes — already handled above
-                if matches!(code.as_str(), "203" | "204" | "205" | "208") {
-                    continue;
-                }
-                obj.insert(code.clone(), json!(format!("{}", val_per_100g * scale)));
+        let mut fields = serde_json::Map::new();
+        let mut update_mask = Vec::new();
+        let mut parsed_entries = Vec::new();
+
+        for (i, (food, serving, quantity)) in items.iter().enumerate() {
+            // Offset each item's timestamp so entry ids stay unique within the batch.
+            let ts = base_ts + i as i64;
+            let entry_id = format!("{}", ts * 1000);
+            let ua_id = format!("{}", ts * 1000 + 1);
+
+            let mut entry =
+                build_searched_food_entry(food, serving, *quantity, &entry_id, &ua_id, &hour, &minute);
+
+            if let Some(obj) = entry.as_object_mut() {
+                self.stamp_version(obj)?;
+                parsed_entries.push((entry_id.clone(), parse_food_entry(date, entry_id.clone(), obj)));
             }
+            fields.insert(entry_id.clone(), to_firestore_value(&entry));
+            update_mask.push(format!("`{}`", entry_id));
         }
 
-        self.write_food_entry(logged_at, entry).await
+        self.firestore
+            .commit(vec![crate::firestore::Write::Update {
+                path: path.clone(),
+                fields,
+                update_mask,
+            }])
+            .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let Some(store) = &self.store {
+            for (entry_id, parsed) in &parsed_entries {
+                store.upsert(
+                    &food_record_id(date, entry_id),
+                    RecordKind::Food,
+                    Utc::now(),
+                    serde_json::to_value(parsed)?,
+                )?;
+            }
+        }
+
+        Ok(())
     }
 
     /// Delete a food entry by removing it from the document.
@@ -702,71 +1143,207 @@ impl MacroFactorClient {
         self.firestore
             .patch_document(&path, fields, &[&field_mask])
             .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let Some(store) = &self.store {
+            store.tombstone(&food_record_id(date, entry_id), RecordKind::Food, Utc::now())?;
+        }
 
         Ok(())
     }
 
-    /// Sync the daily micro-nutrition summary for a given date.
+    /// Delete multiple food entries for a date in a single atomic write.
     ///
-    /// Reads all food entries, filters out deleted ones, sums macros and
-    /// micronutrients, and writes the totals to `micro/{year}`. The app's
-    /// daily summary reads from this collection.
-    pub async fn sync_day(&mut self, date: NaiveDate) -> Result<()> {
+    /// Like [`delete_food_entry`](Self::delete_food_entry), but batches all
+    /// the removals into one Firestore `:commit` instead of N sequential
+    /// PATCHes.
+    pub async fn delete_food_entries(&mut self, date: NaiveDate, entry_ids: &[String]) -> Result<()> {
         let uid = self.get_user_id().await?;
-        let entries = self.get_food_log(date).await?;
+        let date_str = date.format("%Y-%m-%d").to_string();
+        let path = format!("users/{}/food/{}", uid, date_str);
 
-        let mut total_k = 0.0;
-        let mut total_p = 0.0;
-        let mut total_c = 0.0;
-        let mut total_f = 0.0;
-        let mut micros: HashMap<String, f64> = HashMap::new();
+        let update_mask: Vec<String> = entry_ids.iter().map(|id| format!("`{}`", id)).collect();
+        self.firestore
+            .commit(vec![crate::firestore::Write::Update {
+                path: path.clone(),
+                fields: serde_json::Map::new(),
+                update_mask,
+            }])
+            .await?;
+        self.year_doc_cache.remove(&path);
 
-        for entry in &entries {
-            if entry.deleted == Some(true) {
-                continue;
+        if let Some(store) = &self.store {
+            for entry_id in entry_ids {
+                store.tombstone(&food_record_id(date, entry_id), RecordKind::Food, Utc::now())?;
             }
-            total_k += entry.calories().unwrap_or(0.0);
-            total_p += entry.protein().unwrap_or(0.0);
-            total_c += entry.carbs().unwrap_or(0.0);
-            total_f += entry.fat().unwrap_or(0.0);
         }
 
-        // Re-read raw document to get micronutrient fields
+        Ok(())
+    }
+
+    /// Apply typed updates to an existing food-log entry.
+    ///
+    /// Reads the entry's current raw fields, applies each [`FoodEntryUpdate`]
+    /// in order via [`apply_food_entry_update`], and patches only the
+    /// touched entry back to Firestore — callers never hand-assemble the
+    /// `t`/`b`/`c`/`g`/`y`/`w` wire keys or re-derive a multiplier themselves.
+    pub async fn apply_food_update(
+        &mut self,
+        date: NaiveDate,
+        entry_id: &str,
+        updates: &[FoodEntryUpdate],
+    ) -> Result<()> {
+        let uid = self.get_user_id().await?;
+        let date_str = date.format("%Y-%m-%d").to_string();
+        let path = format!("users/{}/food/{}", uid, date_str);
+
+        let doc = self.get_document_cached(&path).await?;
+        let doc_fields = doc
+            .fields
+            .ok_or_else(|| anyhow!("food log document for {} has no fields", date_str))?;
+        let parsed = parse_firestore_fields(&Value::Object(doc_fields));
+        let mut obj = parsed
+            .get(entry_id)
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| anyhow!("no food entry {} on {}", entry_id, date_str))?
+            .clone();
+
+        for update in updates {
+            apply_food_entry_update(&mut obj, update);
+        }
+        self.stamp_version(&mut obj)?;
+
+        let fields = to_firestore_fields(&json!({ entry_id: Value::Object(obj.clone()) }));
+        let field_mask = format!("`{}`", entry_id);
+        self.firestore
+            .patch_document(&path, fields, &[&field_mask])
+            .await?;
+        self.year_doc_cache.remove(&path);
+
+        if let Some(store) = &self.store {
+            let parsed_entry = parse_food_entry(date, entry_id.to_string(), &obj);
+            store.upsert(
+                &food_record_id(date, entry_id),
+                RecordKind::Food,
+                Utc::now(),
+                serde_json::to_value(&parsed_entry)?,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Conflict-free incremental sync for a single day, safe against edits
+    /// made from another device between our read and write.
+    ///
+    /// Fetches the remote `food/{date}` document and merges each entry into
+    /// the local store with last-writer-wins: the entry with the higher
+    /// `(version_counter, version_client_id)` wins, and a soft-deleted entry
+    /// ([`FoodEntryUpdate::MarkDeleted`]) always dominates an equal-or-lower
+    /// version. Only entries whose version actually advanced are re-summed
+    /// into the day's totals, and only the USDA codes that changed as a
+    /// result are patched into `micro/{year}` — so calling this repeatedly
+    /// for the same day is cheap once things settle.
+    ///
+    /// Requires [`with_default_store`](Self::with_default_store) to have
+    /// been configured, since the state being merged against lives in the
+    /// local store.
+    pub async fn sync_incremental(&mut self, date: NaiveDate) -> Result<()> {
+        let uid = self.get_user_id().await?;
         let date_str = date.format("%Y-%m-%d").to_string();
-        let food_path = format!("users/{}/food/{}", uid, date_str);
-        if let Ok(raw) = self.get_raw_document(&food_path).await {
-            if let Some(map) = raw.as_object() {
-                for (key, val) in map {
-                    if key.starts_with('_') {
-                        continue;
-                    }
-                    if let Some(obj) = val.as_object() {
-                        // Skip deleted entries
-                        if obj.get("d").and_then(|v| v.as_bool()) == Some(true) {
-                            continue;
-                        }
-                        let multiplier = Self::compute_multiplier(obj);
-                        for (field, fval) in obj {
-                            if !field.chars().all(|c| c.is_ascii_digit()) {
-                                continue;
-                            }
-                            // Skip main macro codes already handled
-                            if matches!(field.as_str(), "208" | "203" | "204" | "205") {
-                                continue;
-                            }
-                            if let Some(v) = fval
-                                .as_f64()
-                                .or_else(|| fval.as_str().and_then(|s| s.parse().ok()))
-                            {
-                                let scaled = v * multiplier;
-                                *micros.entry(field.clone()).or_default() += scaled;
-                            }
-                        }
-                    }
-                }
+        let path = format!("users/{}/food/{}", uid, date_str);
+
+        // Bypass `year_doc_cache` here: the whole point of this sync is to
+        // catch a concurrent edit/tombstone from another device, which a
+        // recently-cached read of this same document would silently hide.
+        let doc = match self.firestore.get_document(&path).await {
+            Ok(d) => d,
+            Err(e) if e.to_string().contains("404") => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        self.year_doc_cache
+            .insert(path.clone(), (doc.clone(), Instant::now()));
+        let remote_entries = parse_food_entries(date, &doc);
+
+        let store = self.store()?.clone();
+        let old_entries = self.local_food_log(date)?;
+
+        let mut changed_any = false;
+        for remote in remote_entries {
+            let record_id = food_record_id(date, &remote.entry_id);
+            let local: Option<FoodEntry> = store
+                .get(&record_id)?
+                .and_then(|r| r.payload)
+                .and_then(|p| serde_json::from_value(p).ok());
+
+            if remote_wins(&remote, local.as_ref()) && local.as_ref() != Some(&remote) {
+                store.upsert(&record_id, RecordKind::Food, Utc::now(), serde_json::to_value(&remote)?)?;
+                changed_any = true;
             }
         }
 
+        if !changed_any {
+            return Ok(());
+        }
+
+        let new_entries = self.local_food_log(date)?;
+        let old_totals = aggregate_day(&old_entries);
+        let new_totals = aggregate_day(&new_entries);
+
+        let mmdd = date.format("%m%d").to_string();
+        let mut changed_fields = serde_json::Map::new();
+        let mut field_masks: Vec<String> = Vec::new();
+
+        let mut diff = |code: &str, old: f64, new: f64| {
+            if old != new {
+                changed_fields.insert(code.to_string(), json!(format!("{}", new)));
+                field_masks.push(format!("`{}`.`{}`", mmdd, code));
+            }
+        };
+        diff("k", old_totals.calories, new_totals.calories);
+        diff("p", old_totals.protein, new_totals.protein);
+        diff("c", old_totals.carbs, new_totals.carbs);
+        diff("f", old_totals.fat, new_totals.fat);
+
+        let mut codes: Vec<&String> = old_totals
+            .micronutrients
+            .keys()
+            .chain(new_totals.micronutrients.keys())
+            .collect();
+        codes.sort();
+        codes.dedup();
+        for code in codes {
+            let old = old_totals.micronutrients.get(code).copied().unwrap_or(0.0);
+            let new = new_totals.micronutrients.get(code).copied().unwrap_or(0.0);
+            diff(code, old, new);
+        }
+
+        if field_masks.is_empty() {
+            return Ok(());
+        }
+
+        let year = date.format("%Y").to_string();
+        let micro_path = format!("users/{}/micro/{}", uid, year);
+        let fields = to_firestore_fields(&json!({ &mmdd: Value::Object(changed_fields) }));
+        let mask_refs: Vec<&str> = field_masks.iter().map(|s| s.as_str()).collect();
+        self.firestore
+            .patch_document(&micro_path, fields, &mask_refs)
+            .await?;
+        self.year_doc_cache.remove(&micro_path);
+
+        Ok(())
+    }
+
+    /// Sync the daily micro-nutrition summary for a given date.
+    ///
+    /// Reads all food entries, filters out deleted ones, sums macros and
+    /// micronutrients via [`aggregate_day`], and writes the totals to
+    /// `micro/{year}`. The app's daily summary reads from this collection.
+    pub async fn sync_day(&mut self, date: NaiveDate) -> Result<()> {
+        let uid = self.get_user_id().await?;
+        let entries = self.get_food_log(date).await?;
+        let totals = aggregate_day(&entries);
+
         // All micro nutrient codes the app expects
         let all_codes = [
             "209", "221", "255", "262", "269", "291", "301", "303", "304", "305", "306", "307",
@@ -777,14 +1354,14 @@ impl MacroFactorClient {
         ];
 
         let mut entry = serde_json::Map::new();
-        entry.insert("k".to_string(), json!(format!("{}", total_k)));
-        entry.insert("p".to_string(), json!(format!("{}", total_p)));
-        entry.insert("c".to_string(), json!(format!("{}", total_c)));
-        entry.insert("f".to_string(), json!(format!("{}", total_f)));
+        entry.insert("k".to_string(), json!(format!("{}", totals.calories)));
+        entry.insert("p".to_string(), json!(format!("{}", totals.protein)));
+        entry.insert("c".to_string(), json!(format!("{}", totals.carbs)));
+        entry.insert("f".to_string(), json!(format!("{}", totals.fat)));
 
         for code in &all_codes {
             let code_str = code.to_string();
-            if let Some(v) = micros.get(&code_str) {
+            if let Some(v) = totals.micronutrients.get(&code_str) {
                 entry.insert(code_str, json!(format!("{}", v)));
             } else {
                 entry.insert(code_str, Value::Null);
@@ -803,20 +1380,302 @@ impl MacroFactorClient {
 
         Ok(())
     }
+}
 
-    /// Compute the multiplier for a raw food entry object.
-    fn compute_multiplier(obj: &serde_json::Map<String, Value>) -> f64 {
-        let parse = |k: &str| -> Option<f64> {
-            obj.get(k).and_then(|v| {
-                v.as_f64()
-                    .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+/// Build the raw entry JSON (the `t`/`b`/`c`/.../`m` shape) for one logged
+/// search result. Shared by [`MacroFactorClient::log_searched_food`] and
+/// [`MacroFactorClient::log_meal`].
+fn build_searched_food_entry(
+    food: &SearchFoodResult,
+    serving: &FoodServing,
+    quantity: f64,
+    entry_id: &str,
+    ua_id: &str,
+    hour: &str,
+    minute: &str,
+) -> Value {
+    // Serving gram weight (this becomes the "g" field — the base for macro values)
+    let serving_grams = serving.gram_weight;
+    // Scale factor from per-100g to per-serving
+    let scale = serving_grams / 100.0;
+
+    // Grams per one display unit
+    let unit_weight = serving.gram_weight / serving.amount;
+    // Total display units
+    let total_units = quantity * serving.amount;
+
+    let measurements: Vec<Value> = food
+        .servings
+        .iter()
+        .map(|s| {
+            json!({
+                "m": s.description,
+                "q": format!("{:.1}", s.amount),
+                "w": format!("{}", s.gram_weight)
             })
-        };
-        match (parse("g"), parse("y"), parse("w")) {
-            (Some(g), Some(y), Some(w)) if g > 0.0 => (y * w) / g,
-            _ => 1.0,
+        })
+        .collect();
+
+    let mut entry = json!({
+        "t": food.name,
+        "b": food.brand.as_deref().unwrap_or(""),
+        "c": format!("{}", food.calories_per_100g * scale),
+        "p": format!("{}", food.protein_per_100g * scale),
+        "e": format!("{}", food.carbs_per_100g * scale),
+        "f": format!("{}", food.fat_per_100g * scale),
+        "g": format!("{}", serving_grams),
+        "w": format!("{}", unit_weight),
+        "y": format!("{}", total_units),
+        "q": format!("{}", serving.amount),
+        "s": serving.description,
+        "u": serving.description,
+        "h": hour,
+        "mi": minute,
+        "k": "t",
+        "id": food.food_id,
+        "ca": entry_id,
+        "ua": ua_id,
+        "ef": false,
+        "d": false,
+        "o": false,
+        "fav": false,
+        "x": food.image_id.as_deref().unwrap_or("13"),
+        "m": measurements
+    });
+
+    // Copy all micronutrient values, scaled to serving size
+    if let Some(obj) = entry.as_object_mut() {
+        for (code, val_per_100g) in &food.nutrients_per_100g {
+            // Skip the main macro codes — already handled above
+            if matches!(code.as_str(), "203" | "204" | "205" | "208") {
+                continue;
+            }
+            obj.insert(code.clone(), json!(format!("{}", val_per_100g * scale)));
         }
     }
+
+    entry
+}
+
+/// Local-store record id for a `scale/{year}` entry.
+fn weight_record_id(date: NaiveDate) -> String {
+    format!("weight:{}", date)
+}
+
+/// Local-store record id for a `nutrition/{year}` entry.
+fn nutrition_record_id(date: NaiveDate) -> String {
+    format!("nutrition:{}", date)
+}
+
+/// Local-store record id for a `steps/{year}` entry.
+fn steps_record_id(date: NaiveDate) -> String {
+    format!("steps:{}", date)
+}
+
+/// Local-store record id for one entry of a `food/{date}` document.
+fn food_record_id(date: NaiveDate, entry_id: &str) -> String {
+    format!("food:{}:{}", date, entry_id)
+}
+
+/// Parse one raw entry object (the `t`/`b`/`c`/.../`m` shape) from a
+/// `food/{date}` document into a [`FoodEntry`].
+///
+/// Shared by [`parse_food_entries`] and the local-store mirroring in
+/// [`MacroFactorClient::write_food_entry`] etc., which need to turn the same
+/// raw JSON into a `FoodEntry` without re-fetching the document.
+fn parse_food_entry(date: NaiveDate, entry_id: String, obj: &serde_json::Map<String, Value>) -> FoodEntry {
+    let parse_num = |k: &str| -> Option<f64> {
+        obj.get(k)
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+    };
+    let parse_str = |k: &str| obj.get(k).and_then(|v| v.as_str()).map(String::from);
+
+    let micronutrients: HashMap<String, f64> = obj
+        .keys()
+        .filter(|k| k.chars().all(|c| c.is_ascii_digit()))
+        .filter(|k| !matches!(k.as_str(), "208" | "203" | "204" | "205"))
+        .filter_map(|k| parse_num(k).map(|v| (k.clone(), v)))
+        .collect();
+
+    FoodEntry {
+        date,
+        entry_id,
+        name: parse_str("t"),
+        brand: parse_str("b"),
+        calories_raw: parse_num("c"),
+        protein_raw: parse_num("p"),
+        carbs_raw: parse_num("e"),
+        fat_raw: parse_num("f"),
+        serving_grams: parse_num("g"),
+        user_qty: parse_num("y"),
+        unit_weight: parse_num("w"),
+        quantity: parse_num("q"),
+        serving_unit: parse_str("s"),
+        hour: parse_str("h"),
+        minute: parse_str("mi"),
+        source_type: parse_str("k"),
+        food_id: parse_str("id"),
+        deleted: obj.get("d").and_then(|v| v.as_bool()),
+        micronutrients,
+        version_counter: obj.get("vc").and_then(|v| v.as_u64()),
+        version_client_id: parse_str("vi"),
+    }
+}
+
+/// Parse the `users/{uid}/food/{date}` document into sorted [`FoodEntry`]s.
+///
+/// Shared by [`MacroFactorClient::get_food_log`] and
+/// [`MacroFactorClient::watch_food_log`], which re-parses the whole document
+/// on every `Listen` update.
+fn parse_food_entries(date: NaiveDate, doc: &crate::firestore::Document) -> Vec<FoodEntry> {
+    let mut entries = Vec::new();
+
+    if let Some(ref fields) = doc.fields {
+        let parsed = parse_firestore_fields(&Value::Object(fields.clone()));
+        if let Some(map) = parsed.as_object() {
+            for (key, val) in map {
+                if key.starts_with('_') {
+                    continue;
+                }
+                if let Some(obj) = val.as_object() {
+                    entries.push(parse_food_entry(date, key.clone(), obj));
+                }
+            }
+        }
+    }
+
+    // Sort by hour:minute
+    entries.sort_by(|a, b| {
+        let time_a = (
+            a.hour.as_deref().unwrap_or("0").parse::<u32>().unwrap_or(0),
+            a.minute
+                .as_deref()
+                .unwrap_or("0")
+                .parse::<u32>()
+                .unwrap_or(0),
+        );
+        let time_b = (
+            b.hour.as_deref().unwrap_or("0").parse::<u32>().unwrap_or(0),
+            b.minute
+                .as_deref()
+                .unwrap_or("0")
+                .parse::<u32>()
+                .unwrap_or(0),
+        );
+        time_a.cmp(&time_b)
+    });
+
+    entries
+}
+
+/// Which of the `searches` entries a [`FoodCollections`] selection sends,
+/// parallel to the `results` a `multi_search` response returns for them:
+/// `false` for `common_foods`, `true` for `branded_foods`.
+fn collection_branded_flags(collections: FoodCollections) -> Vec<bool> {
+    let mut branded_flags = Vec::new();
+    if matches!(collections, FoodCollections::CommonOnly | FoodCollections::Both) {
+        branded_flags.push(false);
+    }
+    if matches!(collections, FoodCollections::BrandedOnly | FoodCollections::Both) {
+        branded_flags.push(true);
+    }
+    branded_flags
+}
+
+/// Whether `err` represents a failure to reach Typesense at all (connect
+/// failure, timeout) as opposed to a request that reached the server and got
+/// an error response — only the former should fall back to the offline index.
+fn is_connection_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+}
+
+/// Send `query` to Typesense's `multi_search` endpoint and return the raw
+/// response body together with which `results` entries are the branded
+/// collection (parallel to the `searches` array that produced them). Shared
+/// by [`MacroFactorClient::search`] and
+/// [`MacroFactorClient::search_foods_ttl`], which additionally caches the body.
+async fn fetch_typesense_search(query: &FoodSearchQuery) -> Result<(String, Vec<bool>)> {
+    let url = format!("{}/multi_search", TYPESENSE_HOST);
+
+    let mut searches = Vec::new();
+    if matches!(query.collections, FoodCollections::CommonOnly | FoodCollections::Both) {
+        searches.push(build_typesense_search(query, "common_foods", "foodDesc"));
+    }
+    if matches!(query.collections, FoodCollections::BrandedOnly | FoodCollections::Both) {
+        searches.push(build_typesense_search(query, "branded_foods", "foodDesc,brandName"));
+    }
+
+    let body = json!({ "searches": searches });
+
+    let client = Client::new();
+    let resp = client
+        .post(&url)
+        .header("x-typesense-api-key", TYPESENSE_API_KEY)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("Typesense search failed: {} - {}", status, text));
+    }
+
+    Ok((resp.text().await?, collection_branded_flags(query.collections)))
+}
+
+/// Build one `multi_search` search object for [`MacroFactorClient::search`].
+fn build_typesense_search(query: &FoodSearchQuery, collection: &str, query_by: &str) -> Value {
+    let mut search = json!({
+        "collection": collection,
+        "q": query.query,
+        "query_by": query_by,
+        "per_page": query.per_page,
+        "page": query.page,
+    });
+    if let Some(filter_by) = &query.filter_by {
+        search["filter_by"] = json!(filter_by);
+    }
+    if let Some(sort_by) = &query.sort_by {
+        search["sort_by"] = json!(sort_by);
+    }
+    search
+}
+
+/// Parse a Typesense `multi_search` response body into a [`FoodSearchPage`],
+/// given which of the response's `results` entries are the branded
+/// collection (parallel to the `searches` array that produced them).
+fn parse_search_page(data: &Value, branded_flags: &[bool]) -> FoodSearchPage {
+    let mut results = Vec::new();
+    let mut total_common = None;
+    let mut total_branded = None;
+
+    if let Some(searches) = data.get("results").and_then(|v| v.as_array()) {
+        for (search, &branded) in searches.iter().zip(branded_flags) {
+            let found = search.get("found").and_then(|v| v.as_u64()).map(|n| n as u32);
+            if branded {
+                total_branded = found;
+            } else {
+                total_common = found;
+            }
+            if let Some(hits) = search.get("hits").and_then(|v| v.as_array()) {
+                for hit in hits {
+                    if let Some(doc) = hit.get("document") {
+                        if let Some(result) = parse_typesense_hit(doc, branded) {
+                            results.push(result);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    FoodSearchPage {
+        results,
+        total_common,
+        total_branded,
+    }
 }
 
 /// Parse a Typesense document hit into a SearchFoodResult.
@@ -1,9 +1,13 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use anyhow::{anyhow, Result};
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::{json, Map, Value};
 
 use crate::auth::{FirebaseAuth, PROJECT_ID};
+use crate::cache::{cache_key, Cache};
 
 const BASE_URL: &str = "https://firestore.googleapis.com/v1";
 
@@ -11,9 +15,10 @@ const BASE_URL: &str = "https://firestore.googleapis.com/v1";
 pub struct FirestoreClient {
     client: Client,
     auth: FirebaseAuth,
+    cache: Option<Cache>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Document {
     pub name: String,
     pub fields: Option<Map<String, Value>>,
@@ -23,6 +28,21 @@ pub struct Document {
     pub update_time: Option<String>,
 }
 
+/// A single write operation for [`FirestoreClient::commit`].
+#[derive(Debug, Clone)]
+pub enum Write {
+    /// Patch specific fields of a document (creates it if missing). An empty
+    /// `update_mask` applies no field mask (the whole document is replaced);
+    /// an empty `fields` map with a non-empty mask deletes those fields.
+    Update {
+        path: String,
+        fields: Map<String, Value>,
+        update_mask: Vec<String>,
+    },
+    /// Delete a document entirely.
+    Delete { path: String },
+}
+
 #[derive(Debug, Deserialize)]
 struct ListDocumentsResponse {
     documents: Option<Vec<Document>>,
@@ -30,6 +50,13 @@ struct ListDocumentsResponse {
     next_page_token: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct BatchGetResponse {
+    found: Option<Document>,
+    #[allow(dead_code)]
+    missing: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct RunQueryResponse {
     document: Option<Document>,
@@ -51,9 +78,16 @@ impl FirestoreClient {
         Self {
             client: Client::new(),
             auth,
+            cache: None,
         }
     }
 
+    /// Enable on-disk response caching for subsequent `*_cached` reads.
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     fn documents_base(&self) -> String {
         format!(
             "{}/projects/{}/databases/(default)/documents",
@@ -61,24 +95,68 @@ impl FirestoreClient {
         )
     }
 
+    /// Full resource name (`projects/.../documents/{path}`) for a document
+    /// path, as required by [`listen`](Self::listen) targets.
+    pub fn document_full_name(&self, path: &str) -> String {
+        format!(
+            "projects/{}/databases/(default)/documents/{}",
+            PROJECT_ID, path
+        )
+    }
+
     pub async fn get_document(&self, path: &str) -> Result<Document> {
-        let token = self.auth.get_id_token().await?;
+        self.get_document_ttl(path, Duration::ZERO).await
+    }
+
+    /// Like [`get_document`](Self::get_document), but serves a cached copy
+    /// when one exists and is younger than `local_ttl` (requires
+    /// [`with_cache`](Self::with_cache); `local_ttl` of zero always fetches).
+    pub async fn get_document_ttl(&self, path: &str, local_ttl: Duration) -> Result<Document> {
         let url = format!("{}/{}", self.documents_base(), path);
+        let key = cache_key(&url, &[]);
+        let body = self.fetch_cached(&key, local_ttl, &url, &[]).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Fetch many documents in a single `documents:batchGet` request instead
+    /// of one `get_document` round trip each.
+    ///
+    /// Returns one entry per input path, in the same order, `None` where the
+    /// document doesn't exist. Bypasses the on-disk cache — callers that want
+    /// cached reads should check the cache themselves before batching the misses.
+    pub async fn batch_get_documents(&self, paths: &[String]) -> Result<Vec<Option<Document>>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let token = self.auth.get_id_token().await?;
+        let url = format!("{}:batchGet", self.documents_base());
+        let names: Vec<String> = paths.iter().map(|p| self.document_full_name(p)).collect();
+        let body = json!({ "documents": names });
 
         let resp = self
             .client
-            .get(&url)
+            .post(&url)
             .bearer_auth(&token)
+            .json(&body)
             .send()
             .await?;
 
         if !resp.status().is_success() {
             let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("GET {} failed: {} - {}", path, status, body));
+            let text = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("batchGet failed: {} - {}", status, text));
         }
 
-        Ok(resp.json().await?)
+        let results: Vec<BatchGetResponse> = resp.json().await?;
+        // The response isn't guaranteed to preserve request order, so index
+        // found documents by their resource name and look each one back up.
+        let mut by_name: HashMap<String, Document> = results
+            .into_iter()
+            .filter_map(|r| r.found)
+            .map(|doc| (doc.name.clone(), doc))
+            .collect();
+        Ok(names.iter().map(|n| by_name.remove(n)).collect())
     }
 
     pub async fn list_documents(
@@ -87,36 +165,81 @@ impl FirestoreClient {
         page_size: Option<u32>,
         page_token: Option<&str>,
     ) -> Result<(Vec<Document>, Option<String>)> {
-        let token = self.auth.get_id_token().await?;
-        let url = format!("{}/{}", self.documents_base(), collection_path);
+        self.list_documents_ttl(collection_path, page_size, page_token, Duration::ZERO)
+            .await
+    }
 
-        let mut req = self.client.get(&url).bearer_auth(&token);
+    /// Like [`list_documents`](Self::list_documents), but serves a cached
+    /// copy keyed on the full URL (including `pageSize`/`pageToken`) when one
+    /// exists and is younger than `local_ttl`.
+    pub async fn list_documents_ttl(
+        &self,
+        collection_path: &str,
+        page_size: Option<u32>,
+        page_token: Option<&str>,
+        local_ttl: Duration,
+    ) -> Result<(Vec<Document>, Option<String>)> {
+        let url = format!("{}/{}", self.documents_base(), collection_path);
 
+        let mut params: Vec<(String, String)> = Vec::new();
         if let Some(size) = page_size {
-            req = req.query(&[("pageSize", size.to_string())]);
+            params.push(("pageSize".to_string(), size.to_string()));
         }
         if let Some(pt) = page_token {
-            req = req.query(&[("pageToken", pt)]);
+            params.push(("pageToken".to_string(), pt.to_string()));
+        }
+        let param_refs: Vec<(&str, &str)> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let key = cache_key(&url, &param_refs);
+
+        let body = self
+            .fetch_cached(&key, local_ttl, &url, &param_refs)
+            .await?;
+        let list_resp: ListDocumentsResponse = serde_json::from_str(&body)?;
+        Ok((
+            list_resp.documents.unwrap_or_default(),
+            list_resp.next_page_token,
+        ))
+    }
+
+    /// Shared GET-with-cache helper used by the read methods above.
+    async fn fetch_cached(
+        &self,
+        key: &str,
+        local_ttl: Duration,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<String> {
+        if !local_ttl.is_zero() {
+            if let Some(cache) = &self.cache {
+                if let Some(body) = cache.get(key, local_ttl) {
+                    return Ok(body);
+                }
+            }
         }
 
-        let resp = req.send().await?;
+        let token = self.auth.get_id_token().await?;
+        let resp = self
+            .client
+            .get(url)
+            .bearer_auth(&token)
+            .query(params)
+            .send()
+            .await?;
 
         if !resp.status().is_success() {
             let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!(
-                "LIST {} failed: {} - {}",
-                collection_path,
-                status,
-                body
-            ));
+            let text = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("GET {} failed: {} - {}", url, status, text));
         }
 
-        let list_resp: ListDocumentsResponse = resp.json().await?;
-        Ok((
-            list_resp.documents.unwrap_or_default(),
-            list_resp.next_page_token,
-        ))
+        let body = resp.text().await?;
+        if let Some(cache) = &self.cache {
+            cache.set(key, &body)?;
+        }
+        Ok(body)
     }
 
     pub async fn list_collection_ids(
@@ -176,17 +299,36 @@ impl FirestoreClient {
         parent_path: Option<&str>,
         structured_query: Value,
     ) -> Result<Vec<Document>> {
-        let token = self.auth.get_id_token().await?;
+        self.run_query_ttl(parent_path, structured_query, Duration::ZERO)
+            .await
+    }
+
+    /// Like [`run_query`](Self::run_query), but serves a cached copy keyed on
+    /// the URL and query body when one exists and is younger than `local_ttl`.
+    pub async fn run_query_ttl(
+        &self,
+        parent_path: Option<&str>,
+        structured_query: Value,
+        local_ttl: Duration,
+    ) -> Result<Vec<Document>> {
         let parent = match parent_path {
             Some(p) => format!("{}/{}", self.documents_base(), p),
             None => self.documents_base(),
         };
         let url = format!("{}:runQuery", parent);
+        let body = json!({ "structuredQuery": structured_query });
+        let key = cache_key(&url, &[("body", &body.to_string())]);
+
+        if !local_ttl.is_zero() {
+            if let Some(cache) = &self.cache {
+                if let Some(cached) = cache.get(&key, local_ttl) {
+                    let results: Vec<RunQueryResponse> = serde_json::from_str(&cached)?;
+                    return Ok(results.into_iter().filter_map(|r| r.document).collect());
+                }
+            }
+        }
 
-        let body = json!({
-            "structuredQuery": structured_query
-        });
-
+        let token = self.auth.get_id_token().await?;
         let resp = self
             .client
             .post(&url)
@@ -197,11 +339,15 @@ impl FirestoreClient {
 
         if !resp.status().is_success() {
             let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("runQuery failed: {} - {}", status, body));
+            let text = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("runQuery failed: {} - {}", status, text));
         }
 
-        let results: Vec<RunQueryResponse> = resp.json().await?;
+        let text = resp.text().await?;
+        if let Some(cache) = &self.cache {
+            cache.set(&key, &text)?;
+        }
+        let results: Vec<RunQueryResponse> = serde_json::from_str(&text)?;
         Ok(results.into_iter().filter_map(|r| r.document).collect())
     }
 
@@ -239,6 +385,246 @@ impl FirestoreClient {
 
         Ok(resp.json().await?)
     }
+
+    /// Apply many writes atomically via Firestore's `:commit` endpoint —
+    /// either every write lands or none do.
+    pub async fn commit(&self, writes: Vec<Write>) -> Result<()> {
+        let token = self.auth.get_id_token().await?;
+        let url = format!("{}:commit", self.documents_base());
+
+        let writes_json: Vec<Value> = writes
+            .into_iter()
+            .map(|w| match w {
+                Write::Update {
+                    path,
+                    fields,
+                    update_mask,
+                } => {
+                    let mut write = json!({
+                        "update": {
+                            "name": self.document_full_name(&path),
+                            "fields": fields,
+                        }
+                    });
+                    if !update_mask.is_empty() {
+                        write["updateMask"] = json!({ "fieldPaths": update_mask });
+                    }
+                    write
+                }
+                Write::Delete { path } => json!({ "delete": self.document_full_name(&path) }),
+            })
+            .collect();
+
+        let body = json!({ "writes": writes_json });
+
+        let resp = self
+            .client
+            .post(&url)
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("commit failed: {} - {}", status, text));
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe to real-time changes for the given document resource names
+    /// (see [`document_full_name`](Self::document_full_name)).
+    ///
+    /// Backed by Firestore's `Listen` streaming RPC (the REST
+    /// `google.firestore.v1.Firestore/Listen` endpoint): the HTTP connection
+    /// stays open and the stream yields a [`DocumentChange`] as soon as the
+    /// backend observes one, so callers don't have to poll `get_document`.
+    pub fn listen(
+        &self,
+        document_names: Vec<String>,
+    ) -> impl futures_core::Stream<Item = Result<DocumentChange>> + '_ {
+        use futures_util::StreamExt;
+
+        async_stream::try_stream! {
+            let token = self.auth.get_id_token().await?;
+            let url = format!("{}:listen", self.documents_base());
+            let body = json!({
+                "addTarget": {
+                    "documents": { "documents": document_names },
+                    "targetId": 1,
+                }
+            });
+
+            let resp = self.client.post(&url).bearer_auth(&token).json(&body).send().await?;
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let text = resp.text().await.unwrap_or_default();
+                Err(anyhow!("listen failed: {} - {}", status, text))?;
+                return;
+            }
+
+            let mut byte_stream = resp.bytes_stream();
+            let mut buf: Vec<u8> = Vec::new();
+            while let Some(chunk) = byte_stream.next().await {
+                buf.extend_from_slice(&chunk?);
+                for frame in drain_complete_json_objects(&mut buf) {
+                    if let Some(change) = parse_listen_frame(&frame) {
+                        yield change;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A single change observed from a [`FirestoreClient::listen`] stream.
+#[derive(Debug, Clone)]
+pub enum DocumentChange {
+    Added(Document),
+    Modified(Document),
+    /// The document's resource name (it no longer exists or no longer
+    /// matches the watched target).
+    Removed(String),
+    /// A `targetChange` frame: no document changed, but the server reported
+    /// a change in the watched target's state — e.g. that the initial
+    /// snapshot has been fully delivered (`Current`), or that the client
+    /// must discard its state and resync (`Reset`).
+    TargetChange(TargetChange),
+}
+
+/// The `targetChangeType` Firestore's `Listen` RPC reports in a
+/// `targetChange` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetChangeType {
+    NoChange,
+    Add,
+    Remove,
+    /// The targets named in `target_ids` have sent all the documents that
+    /// matched their query as of when they were added — i.e. the initial
+    /// snapshot is complete.
+    Current,
+    /// The server is asking the client to discard any cached state for the
+    /// targets named in `target_ids` and resync from scratch.
+    Reset,
+}
+
+/// The decoded contents of a `targetChange` frame — see
+/// [`DocumentChange::TargetChange`].
+#[derive(Debug, Clone)]
+pub struct TargetChange {
+    pub target_change_type: TargetChangeType,
+    pub target_ids: Vec<i64>,
+    pub removed_target_ids: Vec<i64>,
+    pub resume_token: Option<String>,
+}
+
+/// Decode one framed `ListenResponse` JSON object into a [`DocumentChange`].
+/// Returns `None` for frames this crate doesn't recognize.
+fn parse_listen_frame(frame: &Value) -> Option<DocumentChange> {
+    if let Some(dc) = frame.get("documentChange") {
+        let doc: Document = serde_json::from_value(dc.get("document")?.clone()).ok()?;
+        // Firestore doesn't flag add vs. update directly; a document whose
+        // create and update times match hasn't been modified since creation.
+        return Some(if doc.create_time == doc.update_time {
+            DocumentChange::Added(doc)
+        } else {
+            DocumentChange::Modified(doc)
+        });
+    }
+    if let Some(dd) = frame.get("documentDelete") {
+        let name = dd.get("document")?.as_str()?.to_string();
+        return Some(DocumentChange::Removed(name));
+    }
+    if let Some(dr) = frame.get("documentRemove") {
+        let name = dr.get("document")?.as_str()?.to_string();
+        return Some(DocumentChange::Removed(name));
+    }
+    if let Some(tc) = frame.get("targetChange") {
+        let target_change_type = match tc.get("targetChangeType").and_then(|v| v.as_str()) {
+            Some("ADD") => TargetChangeType::Add,
+            Some("REMOVE") => TargetChangeType::Remove,
+            Some("CURRENT") => TargetChangeType::Current,
+            Some("RESET") => TargetChangeType::Reset,
+            _ => TargetChangeType::NoChange,
+        };
+        let target_ids = parse_i64_array(tc.get("targetIds"));
+        let removed_target_ids = parse_i64_array(tc.get("removedTargetIds"));
+        let resume_token = tc.get("resumeToken").and_then(|v| v.as_str()).map(String::from);
+        return Some(DocumentChange::TargetChange(TargetChange {
+            target_change_type,
+            target_ids,
+            removed_target_ids,
+            resume_token,
+        }));
+    }
+    None
+}
+
+fn parse_i64_array(value: Option<&Value>) -> Vec<i64> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_i64()).collect())
+        .unwrap_or_default()
+}
+
+/// Incrementally extract complete top-level JSON objects from `buf`, which
+/// holds (a prefix of) a Firestore `:listen` response — a single JSON array
+/// of `ListenResponse` objects, pretty-printed with embedded newlines, sent
+/// as the stream's bytes trickle in.
+///
+/// Tracks `{`/`}` nesting depth (ignoring brace-like bytes inside quoted
+/// strings) to find each object's span, ignoring the enclosing `[`, `]`,
+/// `,`, and whitespace. Bytes belonging to extracted objects (and anything
+/// before the first `{`) are drained from `buf`; an incomplete trailing
+/// object is left in place for the next call.
+fn drain_complete_json_objects(buf: &mut Vec<u8>) -> Vec<Value> {
+    let mut objects = Vec::new();
+    let mut consumed = 0usize;
+    let mut depth = 0usize;
+    let mut obj_start = None;
+    let mut in_string = false;
+    let mut escape = false;
+
+    for (i, &b) in buf.iter().enumerate() {
+        if in_string {
+            if escape {
+                escape = false;
+            } else if b == b'\\' {
+                escape = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' => {
+                if depth == 0 {
+                    obj_start = Some(i);
+                }
+                depth += 1;
+            }
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    if let Some(start) = obj_start.take() {
+                        if let Ok(value) = serde_json::from_slice(&buf[start..=i]) {
+                            objects.push(value);
+                        }
+                        consumed = i + 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if consumed > 0 {
+        buf.drain(..consumed);
+    }
+    objects
 }
 
 /// Convert a serde_json::Value into Firestore's typed value format.
@@ -367,3 +753,122 @@ pub fn parse_document(doc: &Document) -> Value {
 
     Value::Object(result)
 }
+
+#[cfg(test)]
+mod listen_tests {
+    use super::*;
+
+    #[test]
+    fn parse_listen_frame_decodes_document_change_as_added_or_modified() {
+        let added = json!({
+            "documentChange": {
+                "document": {
+                    "name": "projects/p/databases/(default)/documents/users/u/food/2024-01-01",
+                    "fields": {},
+                    "createTime": "2024-01-01T00:00:00Z",
+                    "updateTime": "2024-01-01T00:00:00Z"
+                },
+                "targetIds": [1]
+            }
+        });
+        assert!(matches!(parse_listen_frame(&added), Some(DocumentChange::Added(_))));
+
+        let modified = json!({
+            "documentChange": {
+                "document": {
+                    "name": "projects/p/databases/(default)/documents/users/u/food/2024-01-01",
+                    "fields": {},
+                    "createTime": "2024-01-01T00:00:00Z",
+                    "updateTime": "2024-01-02T00:00:00Z"
+                },
+                "targetIds": [1]
+            }
+        });
+        assert!(matches!(parse_listen_frame(&modified), Some(DocumentChange::Modified(_))));
+    }
+
+    #[test]
+    fn parse_listen_frame_decodes_document_delete_and_remove() {
+        let delete = json!({ "documentDelete": { "document": "projects/p/databases/(default)/documents/a" } });
+        assert!(matches!(parse_listen_frame(&delete), Some(DocumentChange::Removed(name)) if name == "projects/p/databases/(default)/documents/a"));
+
+        let remove = json!({ "documentRemove": { "document": "projects/p/databases/(default)/documents/b" } });
+        assert!(matches!(parse_listen_frame(&remove), Some(DocumentChange::Removed(name)) if name == "projects/p/databases/(default)/documents/b"));
+    }
+
+    #[test]
+    fn parse_listen_frame_decodes_target_change() {
+        let frame = json!({
+            "targetChange": {
+                "targetChangeType": "CURRENT",
+                "targetIds": [1, 2],
+                "removedTargetIds": [3],
+                "resumeToken": "abc123"
+            }
+        });
+        let Some(DocumentChange::TargetChange(tc)) = parse_listen_frame(&frame) else {
+            panic!("expected a TargetChange");
+        };
+        assert_eq!(tc.target_change_type, TargetChangeType::Current);
+        assert_eq!(tc.target_ids, vec![1, 2]);
+        assert_eq!(tc.removed_target_ids, vec![3]);
+        assert_eq!(tc.resume_token.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn parse_listen_frame_returns_none_for_unrecognized_frames() {
+        assert!(parse_listen_frame(&json!({ "filter": {} })).is_none());
+    }
+
+    /// A realistic `:listen` response body: a single JSON array, pretty
+    /// printed (so objects span multiple lines), containing a `targetChange`
+    /// frame followed by a `documentChange` frame.
+    const LISTEN_RESPONSE_BODY: &str = r#"[{
+  "targetChange": {
+    "targetChangeType": "CURRENT",
+    "targetIds": [],
+    "resumeToken": "abc123"
+  }
+}
+,{
+  "documentChange": {
+    "document": {
+      "name": "projects/p/databases/(default)/documents/users/u/food/2024-01-01",
+      "fields": {},
+      "createTime": "2024-01-01T00:00:00Z",
+      "updateTime": "2024-01-01T00:00:00Z"
+    },
+    "targetIds": [1]
+  }
+}
+]"#;
+
+    #[test]
+    fn drain_complete_json_objects_extracts_both_frames_fed_as_one_chunk() {
+        let mut buf = LISTEN_RESPONSE_BODY.as_bytes().to_vec();
+        let objects = drain_complete_json_objects(&mut buf);
+        assert_eq!(objects.len(), 2);
+        assert!(objects[0].get("targetChange").is_some());
+        assert!(objects[1].get("documentChange").is_some());
+        // Only the unparsed `]` (and surrounding whitespace) should remain.
+        assert!(!buf.iter().any(|&b| b == b'{' || b == b'}'));
+    }
+
+    #[test]
+    fn drain_complete_json_objects_handles_objects_split_across_chunks() {
+        let bytes = LISTEN_RESPONSE_BODY.as_bytes();
+        // Split at an arbitrary point that lands inside the first object.
+        let split_at = bytes.iter().position(|&b| b == b'{').unwrap() + 20;
+        let (first_chunk, second_chunk) = bytes.split_at(split_at);
+
+        let mut buf = first_chunk.to_vec();
+        let objects = drain_complete_json_objects(&mut buf);
+        assert!(objects.is_empty(), "no object should be complete yet");
+
+        buf.extend_from_slice(second_chunk);
+        let objects = drain_complete_json_objects(&mut buf);
+        assert_eq!(objects.len(), 2);
+        assert!(objects[0].get("targetChange").is_some());
+        assert!(objects[1].get("documentChange").is_some());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod auth;
+pub mod cache;
+pub mod client;
+pub mod firestore;
+pub mod local_search;
+pub mod models;
+pub mod recipe;
+pub mod store;
+pub mod usda;
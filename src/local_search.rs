@@ -0,0 +1,237 @@
+//! Offline, typo-tolerant search over a locally cached set of
+//! [`SearchFoodResult`]s — a fallback for when the live Typesense backend
+//! ([`MacroFactorClient::search`](crate::client::MacroFactorClient::search))
+//! isn't reachable.
+//!
+//! Builds an inverted index (`token -> food indices`) over each food's
+//! `name`+`brand`, then at query time accepts index tokens within a bounded
+//! edit distance of each query token — tighter for short tokens, looser for
+//! long ones — so a typo like "chiken" still finds "Chicken Breast".
+
+use std::collections::HashMap;
+
+use crate::models::SearchFoodResult;
+
+/// An in-memory inverted index over a fixed set of foods, supporting fuzzy
+/// offline search.
+#[derive(Clone)]
+pub struct LocalFoodIndex {
+    foods: Vec<SearchFoodResult>,
+    index: HashMap<String, Vec<usize>>,
+}
+
+impl LocalFoodIndex {
+    /// Build an index over `foods` (e.g. ones the user has logged or
+    /// downloaded for offline use).
+    pub fn build(foods: Vec<SearchFoodResult>) -> Self {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, food) in foods.iter().enumerate() {
+            for token in tokenize(&food.name, food.brand.as_deref()) {
+                index.entry(token).or_default().push(i);
+            }
+        }
+        Self { foods, index }
+    }
+
+    /// Search the index for `query`, returning up to `limit` results ranked
+    /// by match quality (most relevant first).
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchFoodResult> {
+        let query_tokens = tokenize(query, None);
+        if query_tokens.is_empty() {
+            return Vec::new();
+        }
+
+        // Number of distinct query tokens each food matched, and an
+        // accumulated match-quality score (exact/fuzzy + prefix bonus).
+        let mut match_count: HashMap<usize, usize> = HashMap::new();
+        let mut quality: HashMap<usize, f64> = HashMap::new();
+
+        for (qi, qt) in query_tokens.iter().enumerate() {
+            let bound = fuzzy_bound(qt.len());
+            let mut best_for_food: HashMap<usize, f64> = HashMap::new();
+
+            for (token, food_idxs) in &self.index {
+                let dist = if qt == token {
+                    0
+                } else {
+                    match bounded_levenshtein(qt, token, bound) {
+                        Some(d) => d,
+                        None => continue,
+                    }
+                };
+
+                let mut token_score = if dist == 0 { 2.0 } else { 1.0 / (1.0 + dist as f64) };
+                // Prefix matches on the first query token get an extra boost.
+                if qi == 0 && token.starts_with(qt.as_str()) {
+                    token_score += 0.5;
+                }
+
+                for &idx in food_idxs {
+                    let entry = best_for_food.entry(idx).or_insert(0.0);
+                    if token_score > *entry {
+                        *entry = token_score;
+                    }
+                }
+            }
+
+            for (idx, score) in best_for_food {
+                *match_count.entry(idx).or_default() += 1;
+                *quality.entry(idx).or_default() += score;
+            }
+        }
+
+        let mut ranked: Vec<(usize, f64)> = match_count
+            .into_iter()
+            .map(|(idx, count)| {
+                let mut score = count as f64 * 10.0 + quality.get(&idx).copied().unwrap_or(0.0);
+                if self.foods[idx].brand.is_none() {
+                    score += 0.25; // small boost for basic/common foods
+                }
+                (idx, score)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(idx, _)| self.foods[idx].clone())
+            .collect()
+    }
+}
+
+/// Split a food's name and brand into lowercase word tokens.
+fn tokenize(name: &str, brand: Option<&str>) -> Vec<String> {
+    let mut text = name.to_lowercase();
+    if let Some(b) = brand {
+        text.push(' ');
+        text.push_str(&b.to_lowercase());
+    }
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Max edit distance accepted for a query token of length `len`.
+fn fuzzy_bound(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Banded Levenshtein distance between `a` and `b`: only cells within
+/// `bound` of the main diagonal are computed, and a row whose running
+/// minimum already exceeds `bound` aborts early, returning `None`.
+fn bounded_levenshtein(a: &str, b: &str, bound: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > bound {
+        return None;
+    }
+
+    const INF: usize = usize::MAX / 2;
+    let n = b.len();
+
+    let mut prev = vec![INF; n + 1];
+    for (j, slot) in prev.iter_mut().enumerate().take(bound.min(n) + 1) {
+        *slot = j;
+    }
+
+    for i in 1..=a.len() {
+        let lo = i.saturating_sub(bound);
+        let hi = (i + bound).min(n);
+        let mut curr = vec![INF; n + 1];
+        if lo == 0 {
+            curr[0] = i;
+        }
+        let mut row_min = curr[0];
+        for j in lo.max(1)..=hi {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let del = prev[j].saturating_add(1);
+            let ins = curr[j - 1].saturating_add(1);
+            let sub = prev[j - 1].saturating_add(cost);
+            let best = del.min(ins).min(sub);
+            curr[j] = best;
+            row_min = row_min.min(best);
+        }
+        if row_min > bound {
+            return None;
+        }
+        prev = curr;
+    }
+
+    let dist = prev[n];
+    if dist <= bound {
+        Some(dist)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn food(name: &str, brand: Option<&str>) -> SearchFoodResult {
+        SearchFoodResult {
+            food_id: name.to_string(),
+            name: name.to_string(),
+            brand: brand.map(String::from),
+            calories_per_100g: 0.0,
+            protein_per_100g: 0.0,
+            fat_per_100g: 0.0,
+            carbs_per_100g: 0.0,
+            default_serving: None,
+            servings: Vec::new(),
+            image_id: None,
+            nutrients_per_100g: HashMap::new(),
+            source: None,
+            branded: brand.is_some(),
+        }
+    }
+
+    #[test]
+    fn exact_match_ranks_first() {
+        let index = LocalFoodIndex::build(vec![
+            food("Chicken Breast", None),
+            food("Chicken Nuggets", Some("BrandX")),
+        ]);
+        let results = index.search("chicken breast", 5);
+        assert_eq!(results[0].name, "Chicken Breast");
+    }
+
+    #[test]
+    fn tolerates_a_single_typo() {
+        let index = LocalFoodIndex::build(vec![food("Chicken Breast", None)]);
+        let results = index.search("chiken", 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Chicken Breast");
+    }
+
+    #[test]
+    fn short_tokens_require_exact_match() {
+        let index = LocalFoodIndex::build(vec![food("Egg", None)]);
+        assert!(index.search("efg", 5).is_empty());
+        assert_eq!(index.search("egg", 5).len(), 1);
+    }
+
+    #[test]
+    fn bounded_levenshtein_handles_an_empty_string() {
+        assert_eq!(bounded_levenshtein("abc", "", 3), Some(3));
+        assert_eq!(bounded_levenshtein("abc", "", 2), None);
+        assert_eq!(bounded_levenshtein("", "", 0), Some(0));
+    }
+
+    #[test]
+    fn prefers_non_branded_on_tie() {
+        let index = LocalFoodIndex::build(vec![
+            food("Oats", Some("BrandY")),
+            food("Oats", None),
+        ]);
+        let results = index.search("oats", 5);
+        assert_eq!(results[0].brand, None);
+    }
+}
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 
 /// A weight/scale measurement entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,12 +78,99 @@ pub struct SearchFoodResult {
     pub branded: bool,
 }
 
+/// Which Typesense collections a [`FoodSearchQuery`] should search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FoodCollections {
+    CommonOnly,
+    BrandedOnly,
+    #[default]
+    Both,
+}
+
+/// A fluent builder for [`MacroFactorClient::search`](crate::client::MacroFactorClient::search).
+///
+/// Defaults to searching both collections, 10 results per page, page 1, no
+/// filter and Typesense's default relevance ranking — the same shape
+/// [`search_foods`](crate::client::MacroFactorClient::search_foods) used to
+/// hard-code.
+#[derive(Debug, Clone)]
+pub struct FoodSearchQuery {
+    pub(crate) query: String,
+    pub(crate) collections: FoodCollections,
+    pub(crate) per_page: u32,
+    pub(crate) page: u32,
+    pub(crate) filter_by: Option<String>,
+    pub(crate) sort_by: Option<String>,
+}
+
+impl FoodSearchQuery {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            collections: FoodCollections::default(),
+            per_page: 10,
+            page: 1,
+            filter_by: None,
+            sort_by: None,
+        }
+    }
+
+    /// Restrict which collections to search (default: both).
+    pub fn collections(mut self, collections: FoodCollections) -> Self {
+        self.collections = collections;
+        self
+    }
+
+    /// Results per page per collection (default: 10).
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        self.per_page = per_page;
+        self
+    }
+
+    /// Page number, 1-indexed (default: 1).
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// A raw Typesense `filter_by` expression, e.g. `"brandName:=Quest"` or
+    /// a numeric predicate like `"203:>=20"` (protein ≥ 20g per 100g —
+    /// nutrient codes match [`SearchFoodResult::nutrients_per_100g`]).
+    pub fn filter_by(mut self, filter: impl Into<String>) -> Self {
+        self.filter_by = Some(filter.into());
+        self
+    }
+
+    /// Sort by a Typesense field (e.g. `"208"` for calories), ascending or
+    /// descending. Overrides the default relevance ranking.
+    pub fn sort_by(mut self, field: impl Into<String>, ascending: bool) -> Self {
+        self.sort_by = Some(format!("{}:{}", field.into(), if ascending { "asc" } else { "desc" }));
+        self
+    }
+}
+
+/// One page of [`MacroFactorClient::search`](crate::client::MacroFactorClient::search)
+/// results.
+///
+/// Each result already knows which collection it came from via
+/// [`SearchFoodResult::branded`]; `total_common`/`total_branded` are the
+/// full match counts Typesense reports for each searched collection, for
+/// computing how many pages remain.
+#[derive(Debug, Clone)]
+pub struct FoodSearchPage {
+    pub results: Vec<SearchFoodResult>,
+    /// Total matches in `common_foods`, or `None` if that collection wasn't searched.
+    pub total_common: Option<u32>,
+    /// Total matches in `branded_foods`, or `None` if that collection wasn't searched.
+    pub total_branded: Option<u32>,
+}
+
 /// An individual food log entry.
 ///
 /// Raw values (`calories_raw`, `protein_raw`, etc.) are per serving size (`serving_grams`).
 /// Use the accessor methods (`.calories()`, `.protein()`, etc.) to get actual consumed amounts,
 /// which apply the quantity multiplier: `raw * (user_qty * unit_weight) / serving_grams`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FoodEntry {
     pub date: NaiveDate,
     /// Entry timestamp ID
@@ -119,6 +207,18 @@ pub struct FoodEntry {
     pub food_id: Option<String>,
     /// Whether this entry has been deleted
     pub deleted: Option<bool>,
+    /// Micronutrient values per serving size, keyed by USDA nutrient code.
+    /// Scale by [`multiplier`](Self::multiplier) for actual consumed amounts,
+    /// same as `calories_raw`/`protein_raw`/etc.
+    pub micronutrients: HashMap<String, f64>,
+    /// Logical version counter for conflict-free incremental sync (`"vc"`
+    /// field) — monotonically increasing per writing client.
+    pub version_counter: Option<u64>,
+    /// The client id that last wrote this entry's version (`"vi"` field).
+    /// Together with `version_counter` this forms the `(counter, client_id)`
+    /// pair that [`MacroFactorClient::sync_incremental`](crate::client::MacroFactorClient::sync_incremental)
+    /// uses for last-writer-wins merges.
+    pub version_client_id: Option<String>,
 }
 
 impl FoodEntry {
@@ -171,6 +271,257 @@ impl FoodEntry {
     }
 }
 
+/// Sum the actual (post-multiplier) macros of non-deleted food entries.
+///
+/// Pure so it can be unit-tested without a live [`FoodEntry`] fetch — see
+/// [`DaySummary`], which uses it to derive `calories_consumed` etc.
+pub fn aggregate_macros(entries: &[FoodEntry]) -> (f64, f64, f64, f64) {
+    entries
+        .iter()
+        .filter(|e| e.deleted != Some(true))
+        .fold((0.0, 0.0, 0.0, 0.0), |(k, p, c, f), e| {
+            (
+                k + e.calories().unwrap_or(0.0),
+                p + e.protein().unwrap_or(0.0),
+                c + e.carbs().unwrap_or(0.0),
+                f + e.fat().unwrap_or(0.0),
+            )
+        })
+}
+
+/// Totals for a day's food log, as computed by [`aggregate_day`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyNutritionSummary {
+    pub calories: f64,
+    pub protein: f64,
+    pub carbs: f64,
+    pub fat: f64,
+    /// Micronutrient totals keyed by USDA nutrient code.
+    pub micronutrients: HashMap<String, f64>,
+}
+
+/// Fold a day's food entries into a [`DailyNutritionSummary`], skipping
+/// deleted entries and applying each entry's portion-scaling multiplier
+/// (`g`·`y`/`w`) to both macros and micronutrients.
+///
+/// Pure and side-effect-free, so a day's totals can be computed without
+/// ever touching Firestore. [`MacroFactorClient::sync_day`](crate::client::MacroFactorClient::sync_day)
+/// is a thin wrapper that calls this and serializes the result.
+pub fn aggregate_day(entries: &[FoodEntry]) -> DailyNutritionSummary {
+    let (calories, protein, carbs, fat) = aggregate_macros(entries);
+
+    let mut micronutrients: HashMap<String, f64> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.deleted != Some(true)) {
+        let multiplier = entry.multiplier().unwrap_or(1.0);
+        for (code, per_serving) in &entry.micronutrients {
+            *micronutrients.entry(code.clone()).or_default() += per_serving * multiplier;
+        }
+    }
+
+    DailyNutritionSummary {
+        calories,
+        protein,
+        carbs,
+        fat,
+        micronutrients,
+    }
+}
+
+/// Decide, for conflict-free incremental sync, whether `remote` should
+/// replace `local` (`None` if the entry doesn't exist locally yet) under
+/// last-writer-wins: the entry with the higher `(version_counter,
+/// version_client_id)` pair wins, except a soft-deleted entry (see
+/// [`FoodEntryUpdate::MarkDeleted`]) always dominates an equal-or-lower
+/// version on the other side.
+///
+/// Pure so the merge policy can be unit-tested without a live Firestore
+/// document — see [`MacroFactorClient::sync_incremental`](crate::client::MacroFactorClient::sync_incremental).
+pub fn remote_wins(remote: &FoodEntry, local: Option<&FoodEntry>) -> bool {
+    let remote_version = (
+        remote.version_counter.unwrap_or(0),
+        remote.version_client_id.clone().unwrap_or_default(),
+    );
+    let local_version = local
+        .map(|e| {
+            (
+                e.version_counter.unwrap_or(0),
+                e.version_client_id.clone().unwrap_or_default(),
+            )
+        })
+        .unwrap_or_default();
+    let remote_deleted = remote.deleted == Some(true);
+    let local_deleted = local.is_some_and(|e| e.deleted == Some(true));
+
+    match (remote_deleted, local_deleted) {
+        (true, false) => remote_version >= local_version,
+        (false, true) => remote_version > local_version,
+        _ => remote_version >= local_version,
+    }
+}
+
+/// A single typed mutation to one food-log entry, applied by
+/// [`MacroFactorClient::apply_food_update`](crate::client::MacroFactorClient::apply_food_update).
+///
+/// Exists so callers never have to hand-assemble the raw `t`/`b`/`c`/`g`/
+/// `y`/`w`/... wire keys themselves, or re-derive [`FoodEntry::multiplier`] —
+/// see [`apply_food_entry_update`] for where that translation happens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FoodEntryUpdate {
+    /// Log this entry as `amount` of `unit`, totalling `grams` — the raw
+    /// `c`/`p`/`e`/`f` macro values stay anchored to the entry's existing
+    /// serving size (`g`), so the effective multiplier stays `1.0`.
+    SetServing { amount: f64, unit: String, grams: f64 },
+    /// Rescale the consumed weight to `grams`, keeping the existing serving
+    /// size and unit.
+    ScaleTo { grams: f64 },
+    /// Set a micronutrient value (USDA nutrient code), per 100g.
+    SetMicronutrient { code: String, per_100g: f64 },
+    /// Soft-delete: sets the `"d"` flag rather than removing the entry
+    /// field outright (unlike [`MacroFactorClient::delete_food_entry`](crate::client::MacroFactorClient::delete_food_entry)).
+    MarkDeleted,
+}
+
+/// Apply one [`FoodEntryUpdate`] to a raw food-log entry object (the
+/// `t`/`b`/`c`/.../`m` shape stored under an entry id in a `food/{date}`
+/// document), mutating only the fields the update touches.
+///
+/// Pure and side-effect-free so the wire-format translation can be
+/// unit-tested in isolation, without patching a real document.
+pub fn apply_food_entry_update(obj: &mut Map<String, Value>, update: &FoodEntryUpdate) {
+    let parse_num = |obj: &Map<String, Value>, k: &str| -> Option<f64> {
+        obj.get(k)
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+    };
+
+    match update {
+        FoodEntryUpdate::SetServing { amount, unit, grams } => {
+            let unit_weight = if *amount > 0.0 { grams / amount } else { 0.0 };
+            obj.insert("g".to_string(), json!(format!("{}", grams)));
+            obj.insert("w".to_string(), json!(format!("{}", unit_weight)));
+            obj.insert("q".to_string(), json!(format!("{}", amount)));
+            obj.insert("y".to_string(), json!(format!("{}", amount)));
+            obj.insert("s".to_string(), json!(unit));
+            obj.insert("u".to_string(), json!(unit));
+        }
+        FoodEntryUpdate::ScaleTo { grams } => {
+            obj.insert("y".to_string(), json!("1.0"));
+            obj.insert("w".to_string(), json!(format!("{}", grams)));
+        }
+        FoodEntryUpdate::SetMicronutrient { code, per_100g } => {
+            let serving_grams = parse_num(obj, "g").unwrap_or(100.0);
+            let scale = serving_grams / 100.0;
+            obj.insert(code.clone(), json!(format!("{}", per_100g * scale)));
+        }
+        FoodEntryUpdate::MarkDeleted => {
+            obj.insert("d".to_string(), json!(true));
+        }
+    }
+}
+
+#[cfg(test)]
+mod food_entry_update_tests {
+    use super::*;
+
+    fn sample_entry() -> Map<String, Value> {
+        json!({
+            "t": "Chicken breast",
+            "c": "165.0",
+            "p": "31.0",
+            "e": "0.0",
+            "f": "3.6",
+            "g": "100.0",
+            "w": "100.0",
+            "y": "1.0",
+            "q": "1.0",
+            "s": "serving",
+            "u": "serving",
+            "d": false,
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn set_serving_keeps_multiplier_at_one() {
+        let mut obj = sample_entry();
+        apply_food_entry_update(
+            &mut obj,
+            &FoodEntryUpdate::SetServing {
+                amount: 2.0,
+                unit: "oz".to_string(),
+                grams: 56.0,
+            },
+        );
+        assert_eq!(obj.get("g").and_then(|v| v.as_str()), Some("56"));
+        assert_eq!(obj.get("y").and_then(|v| v.as_str()), Some("2"));
+        assert_eq!(obj.get("s").and_then(|v| v.as_str()), Some("oz"));
+        let entry = parse_test_entry(&obj);
+        assert_eq!(entry.multiplier(), Some(1.0));
+    }
+
+    #[test]
+    fn scale_to_rescales_weight() {
+        let mut obj = sample_entry();
+        apply_food_entry_update(&mut obj, &FoodEntryUpdate::ScaleTo { grams: 250.0 });
+        let entry = parse_test_entry(&obj);
+        assert_eq!(entry.weight_grams(), Some(250.0));
+        // Serving size and macro basis are untouched.
+        assert_eq!(entry.serving_grams, Some(100.0));
+    }
+
+    #[test]
+    fn set_micronutrient_scales_to_serving_size() {
+        let mut obj = sample_entry();
+        obj.insert("g".to_string(), json!("200.0"));
+        apply_food_entry_update(
+            &mut obj,
+            &FoodEntryUpdate::SetMicronutrient {
+                code: "291".to_string(),
+                per_100g: 4.0,
+            },
+        );
+        assert_eq!(obj.get("291").and_then(|v| v.as_str()), Some("8"));
+    }
+
+    #[test]
+    fn mark_deleted_sets_flag() {
+        let mut obj = sample_entry();
+        apply_food_entry_update(&mut obj, &FoodEntryUpdate::MarkDeleted);
+        assert_eq!(obj.get("d").and_then(|v| v.as_bool()), Some(true));
+    }
+
+    fn parse_test_entry(obj: &Map<String, Value>) -> FoodEntry {
+        let parse_num = |k: &str| -> Option<f64> {
+            obj.get(k)
+                .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+        };
+        FoodEntry {
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            entry_id: "1".to_string(),
+            name: None,
+            brand: None,
+            calories_raw: parse_num("c"),
+            protein_raw: parse_num("p"),
+            carbs_raw: parse_num("e"),
+            fat_raw: parse_num("f"),
+            serving_grams: parse_num("g"),
+            user_qty: parse_num("y"),
+            unit_weight: parse_num("w"),
+            quantity: parse_num("q"),
+            serving_unit: None,
+            hour: None,
+            minute: None,
+            source_type: None,
+            food_id: None,
+            deleted: obj.get("d").and_then(|v| v.as_bool()),
+            micronutrients: HashMap::new(),
+            version_counter: None,
+            version_client_id: None,
+        }
+    }
+}
+
 /// Daily step count entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepEntry {
@@ -213,3 +564,266 @@ pub struct UserProfile {
     pub weight_units: Option<String>,
     pub calorie_units: Option<String>,
 }
+
+/// Unified view of a single day, composed from the food log, scale, steps,
+/// and goals getters so UI consumers don't have to stitch four endpoints
+/// together and re-derive totals themselves.
+///
+/// Built by [`MacroFactorClient::get_day`](crate::client::MacroFactorClient::get_day).
+/// The `*_consumed` fields are rolled up from `food` via [`aggregate_macros`];
+/// the `*_remaining`/`over_goal`/`adherence_pct` methods below are pure
+/// functions of the struct's fields, so they're unit-testable without a
+/// network call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    /// Sorted food-log entries for the day (including deleted ones).
+    pub food: Vec<FoodEntry>,
+    pub weight: Option<ScaleEntry>,
+    pub steps: Option<StepEntry>,
+    /// The planner's goals, if available (absent if the user has no
+    /// planner set up yet).
+    pub goals: Option<Goals>,
+    pub calories_consumed: f64,
+    pub protein_consumed: f64,
+    pub carbs_consumed: f64,
+    pub fat_consumed: f64,
+}
+
+impl DaySummary {
+    /// This day's goal for `pick` (e.g. `|g| &g.calories`), if goals are set.
+    fn goal_for(&self, pick: impl Fn(&Goals) -> &Vec<f64>) -> Option<f64> {
+        let goals = self.goals.as_ref()?;
+        let dow = self.date.weekday().num_days_from_monday() as usize;
+        pick(goals).get(dow).copied()
+    }
+
+    /// Calories remaining against today's goal (negative if over).
+    pub fn calories_remaining(&self) -> Option<f64> {
+        self.goal_for(|g| &g.calories)
+            .map(|goal| goal - self.calories_consumed)
+    }
+
+    /// Protein remaining against today's goal (negative if over).
+    pub fn protein_remaining(&self) -> Option<f64> {
+        self.goal_for(|g| &g.protein)
+            .map(|goal| goal - self.protein_consumed)
+    }
+
+    /// Carbs remaining against today's goal (negative if over).
+    pub fn carbs_remaining(&self) -> Option<f64> {
+        self.goal_for(|g| &g.carbs)
+            .map(|goal| goal - self.carbs_consumed)
+    }
+
+    /// Fat remaining against today's goal (negative if over).
+    pub fn fat_remaining(&self) -> Option<f64> {
+        self.goal_for(|g| &g.fat)
+            .map(|goal| goal - self.fat_consumed)
+    }
+
+    /// Whether calories consumed have exceeded today's goal.
+    /// `false` if there's no goal to compare against.
+    pub fn over_goal(&self) -> bool {
+        self.calories_remaining().is_some_and(|r| r < 0.0)
+    }
+
+    /// Calories consumed as a percentage of today's goal.
+    pub fn adherence_pct(&self) -> Option<f64> {
+        let goal = self.goal_for(|g| &g.calories)?;
+        if goal <= 0.0 {
+            return None;
+        }
+        Some(self.calories_consumed / goal * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: NaiveDate, calories_consumed: f64, goal: f64) -> DaySummary {
+        DaySummary {
+            date,
+            food: Vec::new(),
+            weight: None,
+            steps: None,
+            goals: Some(Goals {
+                calories: vec![goal; 7],
+                protein: vec![0.0; 7],
+                carbs: vec![0.0; 7],
+                fat: vec![0.0; 7],
+                tdee: None,
+                program_style: None,
+                program_type: None,
+            }),
+            calories_consumed,
+            protein_consumed: 0.0,
+            carbs_consumed: 0.0,
+            fat_consumed: 0.0,
+        }
+    }
+
+    #[test]
+    fn calories_remaining_under_goal() {
+        let d = day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 1500.0, 2000.0);
+        assert_eq!(d.calories_remaining(), Some(500.0));
+        assert!(!d.over_goal());
+    }
+
+    #[test]
+    fn calories_remaining_over_goal() {
+        let d = day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 2500.0, 2000.0);
+        assert_eq!(d.calories_remaining(), Some(-500.0));
+        assert!(d.over_goal());
+    }
+
+    #[test]
+    fn no_goals_means_no_remaining_and_not_over() {
+        let mut d = day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 2500.0, 2000.0);
+        d.goals = None;
+        assert_eq!(d.calories_remaining(), None);
+        assert!(!d.over_goal());
+    }
+
+    #[test]
+    fn adherence_pct() {
+        let d = day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 1000.0, 2000.0);
+        assert_eq!(d.adherence_pct(), Some(50.0));
+    }
+
+    #[test]
+    fn aggregate_macros_skips_deleted_entries() {
+        let mut kept = FoodEntry {
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            entry_id: "1".to_string(),
+            name: None,
+            brand: None,
+            calories_raw: Some(100.0),
+            protein_raw: Some(10.0),
+            carbs_raw: Some(5.0),
+            fat_raw: Some(2.0),
+            serving_grams: None,
+            user_qty: None,
+            unit_weight: None,
+            quantity: None,
+            serving_unit: None,
+            hour: None,
+            minute: None,
+            source_type: None,
+            food_id: None,
+            deleted: Some(false),
+            micronutrients: HashMap::new(),
+            version_counter: None,
+            version_client_id: None,
+        };
+        let mut deleted = kept.clone();
+        deleted.entry_id = "2".to_string();
+        deleted.deleted = Some(true);
+        kept.calories_raw = Some(200.0);
+
+        let (calories, protein, carbs, fat) = aggregate_macros(&[kept, deleted]);
+        assert_eq!(calories, 200.0);
+        assert_eq!(protein, 10.0);
+        assert_eq!(carbs, 5.0);
+        assert_eq!(fat, 2.0);
+    }
+
+    fn food_entry_with_micros(
+        serving_grams: f64,
+        user_qty: f64,
+        unit_weight: f64,
+        micronutrients: HashMap<String, f64>,
+    ) -> FoodEntry {
+        FoodEntry {
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            entry_id: "1".to_string(),
+            name: None,
+            brand: None,
+            calories_raw: Some(100.0),
+            protein_raw: Some(10.0),
+            carbs_raw: Some(5.0),
+            fat_raw: Some(2.0),
+            serving_grams: Some(serving_grams),
+            user_qty: Some(user_qty),
+            unit_weight: Some(unit_weight),
+            quantity: Some(1.0),
+            serving_unit: None,
+            hour: None,
+            minute: None,
+            source_type: None,
+            food_id: None,
+            deleted: Some(false),
+            micronutrients,
+            version_counter: None,
+            version_client_id: None,
+        }
+    }
+
+    #[test]
+    fn aggregate_day_scales_micronutrients_by_multiplier() {
+        let mut micros = HashMap::new();
+        micros.insert("401".to_string(), 10.0);
+        // serving = 100g, consumed = 2 * 100g = 200g -> multiplier 2.0
+        let entry = food_entry_with_micros(100.0, 2.0, 100.0, micros);
+
+        let summary = aggregate_day(&[entry]);
+        assert_eq!(summary.calories, 200.0);
+        assert_eq!(summary.micronutrients.get("401"), Some(&20.0));
+    }
+
+    #[test]
+    fn aggregate_day_skips_deleted_entries() {
+        let mut micros = HashMap::new();
+        micros.insert("401".to_string(), 10.0);
+        let mut entry = food_entry_with_micros(100.0, 1.0, 100.0, micros);
+        entry.deleted = Some(true);
+
+        let summary = aggregate_day(&[entry]);
+        assert_eq!(summary.calories, 0.0);
+        assert!(summary.micronutrients.is_empty());
+    }
+
+    fn versioned_entry(counter: u64, client_id: &str, deleted: bool) -> FoodEntry {
+        let mut entry = food_entry_with_micros(100.0, 1.0, 100.0, HashMap::new());
+        entry.version_counter = Some(counter);
+        entry.version_client_id = Some(client_id.to_string());
+        entry.deleted = Some(deleted);
+        entry
+    }
+
+    #[test]
+    fn remote_wins_with_no_local_entry() {
+        let remote = versioned_entry(1, "a", false);
+        assert!(remote_wins(&remote, None));
+    }
+
+    #[test]
+    fn remote_wins_with_higher_counter() {
+        let local = versioned_entry(1, "a", false);
+        let remote = versioned_entry(2, "a", false);
+        assert!(remote_wins(&remote, Some(&local)));
+        assert!(!remote_wins(&local, Some(&remote)));
+    }
+
+    #[test]
+    fn remote_deletion_beats_equal_version_local_edit() {
+        let local = versioned_entry(3, "a", false);
+        let remote = versioned_entry(3, "a", true);
+        assert!(remote_wins(&remote, Some(&local)));
+    }
+
+    #[test]
+    fn local_deletion_beats_equal_version_remote_edit() {
+        let local = versioned_entry(3, "a", true);
+        let remote = versioned_entry(3, "a", false);
+        assert!(!remote_wins(&remote, Some(&local)));
+    }
+
+    #[test]
+    fn local_deletion_loses_to_strictly_higher_remote_version() {
+        let local = versioned_entry(3, "a", true);
+        let remote = versioned_entry(4, "a", false);
+        assert!(remote_wins(&remote, Some(&local)));
+    }
+}
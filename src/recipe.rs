@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::models::{FoodServing, SearchFoodResult};
+
+/// Parse a [`SearchFoodResult`] from a recipe/product page's embedded
+/// schema.org JSON-LD nutrition information (a `Recipe.nutrition` or a
+/// standalone `NutritionInformation` node, optionally nested in `@graph`).
+pub fn parse_recipe_html(html: &str, source_url: &str) -> Result<SearchFoodResult> {
+    for block in extract_json_ld_blocks(html) {
+        let Ok(value) = serde_json::from_str::<Value>(&block) else {
+            continue;
+        };
+        // A JSON-LD script tag can itself hold an array of top-level nodes.
+        let candidates: Vec<&Value> = match &value {
+            Value::Array(arr) => arr.iter().collect(),
+            other => vec![other],
+        };
+        for candidate in candidates {
+            if let Some((recipe_node, nutrition)) = find_nutrition(candidate) {
+                if let Some(result) = build_result(recipe_node, nutrition, source_url) {
+                    return Ok(result);
+                }
+            }
+        }
+    }
+    Err(anyhow!(
+        "no parseable schema.org nutrition information found at {}",
+        source_url
+    ))
+}
+
+/// Extract the raw text contents of every `<script type="application/ld+json">` block.
+fn extract_json_ld_blocks(html: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut rest = html;
+    while let Some(open) = rest.find("<script") {
+        let after_tag = &rest[open..];
+        let Some(tag_end) = after_tag.find('>') else {
+            break;
+        };
+        let tag = &after_tag[..tag_end];
+        if !tag.contains("application/ld+json") {
+            rest = &after_tag[tag_end + 1..];
+            continue;
+        }
+        let body_start = tag_end + 1;
+        let Some(close) = after_tag[body_start..].find("</script>") else {
+            break;
+        };
+        blocks.push(after_tag[body_start..body_start + close].to_string());
+        rest = &after_tag[body_start + close + "</script>".len()..];
+    }
+    blocks
+}
+
+/// Find the first `NutritionInformation` reachable from a JSON-LD node:
+/// either the node itself, a `Recipe`'s `nutrition` field, or (recursively)
+/// an entry of an `@graph` array. Returns `(recipe_or_nutrition_node, nutrition_node)`.
+fn find_nutrition(value: &Value) -> Option<(&Value, &Value)> {
+    if has_type(value, "NutritionInformation") {
+        return Some((value, value));
+    }
+    if has_type(value, "Recipe") {
+        if let Some(n) = value.get("nutrition") {
+            if has_type(n, "NutritionInformation") {
+                return Some((value, n));
+            }
+        }
+    }
+    if let Some(graph) = value.get("@graph").and_then(|g| g.as_array()) {
+        for node in graph {
+            if let Some(found) = find_nutrition(node) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn has_type(value: &Value, want: &str) -> bool {
+    match value.get("@type") {
+        Some(Value::String(s)) => s == want,
+        Some(Value::Array(arr)) => arr.iter().any(|t| t.as_str() == Some(want)),
+        _ => false,
+    }
+}
+
+/// Parse the leading numeric magnitude out of strings like `"12 g"` or `"250"`.
+fn parse_leading_number(s: &str) -> Option<f64> {
+    let trimmed = s.trim();
+    let end = trimmed
+        .char_indices()
+        .find(|(_, c)| !(c.is_ascii_digit() || *c == '.'))
+        .map(|(i, _)| i)
+        .unwrap_or(trimmed.len());
+    trimmed[..end].parse().ok()
+}
+
+fn nutrient(node: &Value, key: &str) -> Option<f64> {
+    let v = node.get(key)?;
+    v.as_f64()
+        .or_else(|| v.as_str().and_then(parse_leading_number))
+}
+
+/// Parse grams out of a schema.org `servingSize`, which may be a plain
+/// string (`"250 g"`) or a `QuantitativeValue` object with a `value`.
+fn parse_serving_grams(value: &Value) -> Option<f64> {
+    match value {
+        Value::String(s) => {
+            let lower = s.to_lowercase();
+            let idx = lower.find('g')?;
+            parse_leading_number(&s[..idx])
+        }
+        Value::Object(_) => value.get("value").and_then(|v| {
+            v.as_f64()
+                .or_else(|| v.as_str().and_then(parse_leading_number))
+        }),
+        _ => None,
+    }
+}
+
+/// Parse a `Recipe`'s `recipeYield` (a plain number, a numeric string
+/// (`"4"`), a descriptive string (`"4 servings"`), or an array of those) into
+/// a serving count.
+fn parse_recipe_yield_count(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(_) => value.as_f64(),
+        Value::String(s) => parse_leading_number(s),
+        Value::Array(arr) => arr.iter().find_map(parse_recipe_yield_count),
+        _ => None,
+    }
+}
+
+fn build_result(recipe_node: &Value, nutrition: &Value, source_url: &str) -> Option<SearchFoodResult> {
+    let calories = nutrient(nutrition, "calories")?;
+    let protein = nutrient(nutrition, "proteinContent").unwrap_or(0.0);
+    let carbs = nutrient(nutrition, "carbohydrateContent").unwrap_or(0.0);
+    let fat = nutrient(nutrition, "fatContent").unwrap_or(0.0);
+    let sugar = nutrient(nutrition, "sugarContent");
+    let fiber = nutrient(nutrition, "fiberContent");
+
+    let name = recipe_node
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Imported recipe")
+        .to_string();
+
+    // schema.org NutritionInformation values describe one `servingSize`.
+    // When servingSize gives a gram weight we can normalize to per-100g;
+    // otherwise, divide an 100g-sized whole recipe across the `Recipe`'s
+    // `recipeYield` serving count; only when neither is available do we fall
+    // back to assuming a single ~100g serving.
+    let serving_grams = nutrition
+        .get("servingSize")
+        .and_then(parse_serving_grams)
+        .or_else(|| {
+            recipe_node
+                .get("recipeYield")
+                .and_then(parse_recipe_yield_count)
+                .filter(|&count| count > 0.0)
+                .map(|count| 100.0 / count)
+        })
+        .unwrap_or(100.0);
+    let scale_to_100g = 100.0 / serving_grams;
+
+    let mut nutrients_per_100g = HashMap::new();
+    if let Some(v) = sugar {
+        nutrients_per_100g.insert("269".to_string(), v * scale_to_100g);
+    }
+    if let Some(v) = fiber {
+        nutrients_per_100g.insert("291".to_string(), v * scale_to_100g);
+    }
+
+    let serving = FoodServing {
+        description: "serving".to_string(),
+        amount: 1.0,
+        gram_weight: serving_grams,
+    };
+
+    let mut hasher = DefaultHasher::new();
+    source_url.hash(&mut hasher);
+    let food_id = format!("url_{:x}", hasher.finish());
+
+    Some(SearchFoodResult {
+        food_id,
+        name,
+        brand: None,
+        calories_per_100g: calories * scale_to_100g,
+        protein_per_100g: protein * scale_to_100g,
+        fat_per_100g: fat * scale_to_100g,
+        carbs_per_100g: carbs * scale_to_100g,
+        default_serving: Some(serving.clone()),
+        servings: vec![serving],
+        image_id: None,
+        nutrients_per_100g,
+        source: Some("recipe_url".to_string()),
+        branded: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn uses_serving_size_grams_when_present() {
+        let recipe = json!({"@type": "Recipe", "name": "Soup", "recipeYield": "4 servings"});
+        let nutrition = json!({
+            "@type": "NutritionInformation",
+            "calories": "200",
+            "servingSize": "250 g",
+        });
+        let result = build_result(&recipe, &nutrition, "https://example.com").unwrap();
+        assert_eq!(result.default_serving.unwrap().gram_weight, 250.0);
+        assert_eq!(result.calories_per_100g, 80.0);
+    }
+
+    #[test]
+    fn falls_back_to_recipe_yield_when_serving_size_has_no_gram_weight() {
+        let recipe = json!({"@type": "Recipe", "name": "Soup", "recipeYield": "4 servings"});
+        let nutrition = json!({
+            "@type": "NutritionInformation",
+            "calories": "200",
+            "servingSize": "1 bowl",
+        });
+        let result = build_result(&recipe, &nutrition, "https://example.com").unwrap();
+        assert_eq!(result.default_serving.unwrap().gram_weight, 25.0);
+        assert_eq!(result.calories_per_100g, 800.0);
+    }
+
+    #[test]
+    fn falls_back_to_recipe_yield_when_serving_size_is_absent() {
+        let recipe = json!({"@type": "Recipe", "name": "Soup", "recipeYield": "5"});
+        let nutrition = json!({"@type": "NutritionInformation", "calories": "100"});
+        let result = build_result(&recipe, &nutrition, "https://example.com").unwrap();
+        assert_eq!(result.default_serving.unwrap().gram_weight, 20.0);
+    }
+
+    #[test]
+    fn falls_back_to_100g_when_neither_serving_size_nor_recipe_yield_present() {
+        let recipe = json!({"@type": "Recipe", "name": "Soup"});
+        let nutrition = json!({"@type": "NutritionInformation", "calories": "100"});
+        let result = build_result(&recipe, &nutrition, "https://example.com").unwrap();
+        assert_eq!(result.default_serving.unwrap().gram_weight, 100.0);
+    }
+
+    #[test]
+    fn ignores_recipe_yield_on_a_standalone_nutrition_information_node() {
+        // When the JSON-LD node itself is the `NutritionInformation` (no
+        // enclosing `Recipe`), `recipe_node` and `nutrition` are the same
+        // value and never carry a `recipeYield`.
+        let nutrition = json!({"@type": "NutritionInformation", "calories": "100"});
+        let result = build_result(&nutrition, &nutrition, "https://example.com").unwrap();
+        assert_eq!(result.default_serving.unwrap().gram_weight, 100.0);
+    }
+}
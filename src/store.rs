@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write as IoWrite};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Which Firestore collection a [`Record`] mirrors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RecordKind {
+    Weight,
+    Nutrition,
+    Steps,
+    Food,
+}
+
+/// One line of the append-only local log.
+///
+/// A `payload` of `None` is a tombstone: it marks `id` as deleted without
+/// rewriting or truncating the file. Replaying the log in order (inserting
+/// on `Some`, removing on `None`) reconstructs the latest state for every id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub id: String,
+    pub logged_at: DateTime<Utc>,
+    pub kind: RecordKind,
+    pub payload: Option<Value>,
+}
+
+/// Durable offline mirror of weight/nutrition/steps/food data.
+///
+/// Backed by a single append-only JSON-lines file under the OS data
+/// directory (e.g. `~/.local/share/macro-factor-rs/store.jsonl` on Linux).
+/// Every write is one `fs::write`-free `append` call, so a crash mid-write
+/// can lose at most the in-flight line, never corrupt prior history.
+#[derive(Clone)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    /// Open (creating if necessary) the default store file under the OS
+    /// data directory.
+    pub fn open_default() -> Result<Self> {
+        let dir = dirs::data_dir()
+            .ok_or_else(|| anyhow!("could not determine OS data directory"))?
+            .join("macro-factor-rs");
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join("store.jsonl"),
+        })
+    }
+
+    /// Open a store backed by an explicit file path.
+    pub fn open(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Replay the log into a `HashMap` reflecting the latest state: inserted
+    /// on a normal record, removed when a tombstone for the same id follows.
+    fn load(&self) -> Result<HashMap<String, Record>> {
+        let Ok(file) = std::fs::File::open(&self.path) else {
+            return Ok(HashMap::new());
+        };
+        let mut state = HashMap::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let record: Record = serde_json::from_str(&line)?;
+            match record.payload {
+                Some(_) => {
+                    state.insert(record.id.clone(), record);
+                }
+                None => {
+                    state.remove(&record.id);
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    fn append(&self, record: &Record) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Upsert `payload` for `id`, appending one log line.
+    pub fn upsert(&self, id: &str, kind: RecordKind, logged_at: DateTime<Utc>, payload: Value) -> Result<()> {
+        self.append(&Record {
+            id: id.to_string(),
+            logged_at,
+            kind,
+            payload: Some(payload),
+        })
+    }
+
+    /// Mark `id` as deleted by appending a tombstone line.
+    pub fn tombstone(&self, id: &str, kind: RecordKind, logged_at: DateTime<Utc>) -> Result<()> {
+        self.append(&Record {
+            id: id.to_string(),
+            logged_at,
+            kind,
+            payload: None,
+        })
+    }
+
+    /// The latest-state record for `id`, if it exists and hasn't been
+    /// tombstoned.
+    pub fn get(&self, id: &str) -> Result<Option<Record>> {
+        Ok(self.load()?.remove(id))
+    }
+
+    /// All live (non-tombstoned) records of a given kind.
+    pub fn records(&self, kind: RecordKind) -> Result<Vec<Record>> {
+        Ok(self
+            .load()?
+            .into_values()
+            .filter(|r| r.kind == kind)
+            .collect())
+    }
+
+    /// Delete the entire log.
+    pub fn clear(&self) -> Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// A stable id for this local store, used as the client half of the
+    /// `(counter, client_id)` logical version stamped on entries for
+    /// conflict-free incremental sync. Generated once and persisted
+    /// alongside the log file.
+    pub fn client_id(&self) -> Result<String> {
+        let path = self.path.with_extension("client_id");
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            let existing = existing.trim();
+            if !existing.is_empty() {
+                return Ok(existing.to_string());
+            }
+        }
+        use std::collections::hash_map::RandomState;
+        use std::hash::{BuildHasher, Hasher};
+        let id = format!("{:016x}", RandomState::new().build_hasher().finish());
+        std::fs::write(&path, &id)?;
+        Ok(id)
+    }
+
+    /// The next value in this store's monotonically increasing version
+    /// counter, persisted alongside the log file so it survives restarts.
+    pub fn next_counter(&self) -> Result<u64> {
+        let path = self.path.with_extension("counter");
+        let current: u64 = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0);
+        let next = current + 1;
+        std::fs::write(&path, next.to_string())?;
+        Ok(next)
+    }
+}
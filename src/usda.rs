@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::models::{FoodServing, SearchFoodResult};
+
+/// FDC nutrient IDs for the four macros this crate tracks as headline
+/// per-100g fields rather than entries in `nutrients_per_100g`.
+const ENERGY_KCAL_ID: u64 = 1008;
+const PROTEIN_ID: u64 = 1003;
+const FAT_ID: u64 = 1004;
+const CARB_ID: u64 = 1005;
+
+/// Parse a single USDA FoodData Central "food" JSON object (as returned by
+/// the `/v1/food/{fdcId}` and `/v1/foods/search` endpoints, or found inside
+/// a bulk full/abridged download) into a [`SearchFoodResult`].
+///
+/// FDC's `foodNutrients[].nutrient.number` is the same nutrient-code scheme
+/// already used throughout this crate (`"269"` = sugar, `"291"` = fiber,
+/// etc. — see [`crate::models::SearchFoodResult::nutrients_per_100g`]), so
+/// every micronutrient FDC reports is carried over directly without a
+/// separate lookup table; only the four headline macros get pulled out
+/// into their own fields.
+pub fn parse_fdc_food(food: &Value) -> Result<SearchFoodResult> {
+    let fdc_id = food
+        .get("fdcId")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| anyhow!("FDC food JSON missing fdcId"))?;
+    let name = food
+        .get("description")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("FDC food JSON missing description"))?
+        .to_string();
+    let brand = food
+        .get("brandOwner")
+        .or_else(|| food.get("brandName"))
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    let mut calories = 0.0;
+    let mut protein = 0.0;
+    let mut fat = 0.0;
+    let mut carbs = 0.0;
+    let mut nutrients_per_100g = HashMap::new();
+
+    let food_nutrients = food
+        .get("foodNutrients")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten();
+    for entry in food_nutrients {
+        let Some(amount) = entry.get("amount").and_then(|v| v.as_f64()) else {
+            continue;
+        };
+        // The full/abridged bulk download nests nutrient metadata under
+        // "nutrient"; the search endpoint flattens it onto the entry itself.
+        let nutrient = entry.get("nutrient").unwrap_or(entry);
+        let id = nutrient
+            .get("id")
+            .or_else(|| nutrient.get("nutrientId"))
+            .and_then(|v| v.as_u64());
+
+        match id {
+            Some(ENERGY_KCAL_ID) => calories = amount,
+            Some(PROTEIN_ID) => protein = amount,
+            Some(FAT_ID) => fat = amount,
+            Some(CARB_ID) => carbs = amount,
+            _ => {
+                if let Some(code) = nutrient.get("number").and_then(|v| v.as_str()) {
+                    nutrients_per_100g.insert(code.to_string(), amount);
+                }
+            }
+        }
+    }
+
+    let servings: Vec<FoodServing> = food
+        .get("foodPortions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(parse_portion)
+        .collect();
+    let default_serving = servings.first().cloned();
+    let branded = brand.is_some();
+
+    Ok(SearchFoodResult {
+        food_id: format!("fdc_{}", fdc_id),
+        name,
+        brand,
+        calories_per_100g: calories,
+        protein_per_100g: protein,
+        fat_per_100g: fat,
+        carbs_per_100g: carbs,
+        default_serving,
+        servings,
+        image_id: None,
+        nutrients_per_100g,
+        source: Some("FDC".to_string()),
+        branded,
+    })
+}
+
+/// Parse one `foodPortions[]` entry into a [`FoodServing`].
+fn parse_portion(portion: &Value) -> Option<FoodServing> {
+    let gram_weight = portion.get("gramWeight").and_then(|v| v.as_f64())?;
+    let amount = portion.get("amount").and_then(|v| v.as_f64()).unwrap_or(1.0);
+    let description = portion
+        .get("modifier")
+        .or_else(|| portion.get("portionDescription"))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("serving")
+        .to_string();
+    Some(FoodServing {
+        description,
+        amount,
+        gram_weight,
+    })
+}
+
+/// Parse an FDC bulk JSON export — either a bare array of food objects, or
+/// an object with the foods under a top-level key (e.g.
+/// `{"FoundationFoods": [...]}`, `{"SRLegacyFoods": [...]}`) — into
+/// [`SearchFoodResult`]s, skipping any entry that fails to parse.
+pub fn parse_fdc_export(export: &Value) -> Vec<SearchFoodResult> {
+    let foods: Vec<&Value> = match export {
+        Value::Array(arr) => arr.iter().collect(),
+        Value::Object(map) => map
+            .values()
+            .find_map(|v| v.as_array())
+            .map(|arr| arr.iter().collect())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    foods.iter().filter_map(|f| parse_fdc_food(f).ok()).collect()
+}
+
+/// Parse a flattened, single-row-per-food FDC CSV export (one food per
+/// line, a header naming each column) into [`SearchFoodResult`]s.
+///
+/// Recognizes `fdc_id`/`description`/`brand_owner` columns for identity and
+/// `energy_kcal`/`protein_g`/`fat_g`/`carbohydrate_g` (case-insensitive, with
+/// or without the unit suffix) for the headline macros; any other column
+/// whose header is purely numeric is treated as a USDA nutrient code and
+/// copied straight into `nutrients_per_100g`, mirroring [`parse_fdc_food`].
+/// Does not handle quoted fields containing commas — USDA's own bulk CSV
+/// exports are multi-file/normalized and need joining before they fit this
+/// shape; this covers flattened single-file exports.
+pub fn parse_fdc_csv(csv: &str) -> Vec<SearchFoodResult> {
+    let mut lines = csv.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<String> = header.split(',').map(|c| c.trim().to_lowercase()).collect();
+
+    let find_col = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+    let id_col = find_col(&["fdc_id", "fdcid"]);
+    let name_col = find_col(&["description", "name"]);
+    let brand_col = find_col(&["brand_owner", "brandowner", "brand"]);
+    let calories_col = find_col(&["energy_kcal", "energy", "calories"]);
+    let protein_col = find_col(&["protein_g", "protein"]);
+    let fat_col = find_col(&["fat_g", "total_fat_g", "fat"]);
+    let carb_col = find_col(&["carbohydrate_g", "carbohydrate", "carbs_g", "carbs"]);
+
+    let Some(name_col) = name_col else {
+        return Vec::new();
+    };
+
+    let get = |fields: &[&str], col: Option<usize>| -> Option<String> {
+        col.and_then(|i| fields.get(i)).map(|s| s.trim().to_string())
+    };
+    let get_num = |fields: &[&str], col: Option<usize>| -> f64 {
+        get(fields, col).and_then(|s| s.parse().ok()).unwrap_or(0.0)
+    };
+
+    let mut results = Vec::new();
+    for (row_idx, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        let Some(name) = get(&fields, Some(name_col)) else {
+            continue;
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let brand = get(&fields, brand_col).filter(|s| !s.is_empty());
+        let branded = brand.is_some();
+        let food_id = get(&fields, id_col).unwrap_or_else(|| format!("fdc_csv_{}", row_idx));
+
+        let mut nutrients_per_100g = HashMap::new();
+        for (i, header_col) in columns.iter().enumerate() {
+            if header_col.chars().all(|c| c.is_ascii_digit()) {
+                if let Some(v) = get(&fields, Some(i)).and_then(|s| s.parse::<f64>().ok()) {
+                    nutrients_per_100g.insert(header_col.clone(), v);
+                }
+            }
+        }
+
+        results.push(SearchFoodResult {
+            food_id: format!("fdc_{}", food_id),
+            name,
+            brand,
+            calories_per_100g: get_num(&fields, calories_col),
+            protein_per_100g: get_num(&fields, protein_col),
+            fat_per_100g: get_num(&fields, fat_col),
+            carbs_per_100g: get_num(&fields, carb_col),
+            default_serving: None,
+            servings: Vec::new(),
+            image_id: None,
+            nutrients_per_100g,
+            source: Some("FDC".to_string()),
+            branded,
+        });
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_macros_and_micros_from_fdc_food() {
+        let food = json!({
+            "fdcId": 123456,
+            "description": "Chicken breast, raw",
+            "foodNutrients": [
+                {"amount": 120.0, "nutrient": {"id": 1008, "number": "208"}},
+                {"amount": 22.5, "nutrient": {"id": 1003, "number": "203"}},
+                {"amount": 2.6, "nutrient": {"id": 1004, "number": "204"}},
+                {"amount": 0.0, "nutrient": {"id": 1005, "number": "205"}},
+                {"amount": 0.04, "nutrient": {"id": 1092, "number": "306"}},
+            ],
+            "foodPortions": [
+                {"amount": 1.0, "modifier": "breast", "gramWeight": 172.0}
+            ]
+        });
+
+        let result = parse_fdc_food(&food).unwrap();
+        assert_eq!(result.food_id, "fdc_123456");
+        assert_eq!(result.calories_per_100g, 120.0);
+        assert_eq!(result.protein_per_100g, 22.5);
+        assert_eq!(result.fat_per_100g, 2.6);
+        assert_eq!(result.carbs_per_100g, 0.0);
+        assert_eq!(result.nutrients_per_100g.get("306"), Some(&0.04));
+        assert!(!result.nutrients_per_100g.contains_key("208"));
+        assert_eq!(result.servings.len(), 1);
+        assert_eq!(result.servings[0].gram_weight, 172.0);
+        assert!(!result.branded);
+    }
+
+    #[test]
+    fn parses_brand_owner_as_branded() {
+        let food = json!({
+            "fdcId": 1,
+            "description": "Granola bar",
+            "brandOwner": "Acme Foods",
+            "foodNutrients": []
+        });
+        let result = parse_fdc_food(&food).unwrap();
+        assert_eq!(result.brand.as_deref(), Some("Acme Foods"));
+        assert!(result.branded);
+    }
+
+    #[test]
+    fn export_skips_unparseable_entries() {
+        let export = json!({
+            "FoundationFoods": [
+                {"fdcId": 1, "description": "Food A", "foodNutrients": []},
+                {"description": "missing fdcId"},
+            ]
+        });
+        let results = parse_fdc_export(&export);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Food A");
+    }
+
+    #[test]
+    fn csv_maps_numeric_headers_to_nutrient_codes() {
+        let csv = "fdc_id,description,brand_owner,energy_kcal,protein_g,fat_g,carbohydrate_g,306\n\
+                    1,Banana,,89,1.1,0.3,22.8,0.36\n";
+        let results = parse_fdc_csv(csv);
+        assert_eq!(results.len(), 1);
+        let banana = &results[0];
+        assert_eq!(banana.name, "Banana");
+        assert_eq!(banana.calories_per_100g, 89.0);
+        assert_eq!(banana.nutrients_per_100g.get("306"), Some(&0.36));
+        assert!(!banana.branded);
+    }
+}